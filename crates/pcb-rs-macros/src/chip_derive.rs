@@ -2,21 +2,44 @@ use proc_macro2::TokenStream;
 use quote::quote;
 
 const PIN_ATTRIBUTE: &str = "pin";
+const COMBINATIONAL_ATTRIBUTE: &str = "combinational";
+const RESET_ATTRIBUTE: &str = "reset";
+const STATE_ATTRIBUTE: &str = "state";
+const BUS_ATTRIBUTE: &str = "bus";
+
+const INVALID_RESET_ERR: &str =
+    "invalid reset attribute, expected #[reset] on the struct and #[reset = <literal>] or #[reset(<value>)] on fields";
+
+const RESET_NOT_ENABLED_ERR: &str =
+    "#[reset = <literal>] and #[reset(<value>)] on fields require the struct to have #[reset] attribute";
+
+const ENUM_RESET_ERR: &str =
+    "#[reset = <literal>] and #[reset(<value>)] are not supported on fields of enums, #[reset] on an enum resets it to its default value";
+
+const ENUM_STATE_ERR: &str =
+    "#[state] is not supported on fields of enums, the saved state of enum chips only has their pins";
 
 const INVALID_PIN_ATTR_ERR: &str =
-    "invalid pin attribute, currently only #[pin(input|output|io,latch)] is supported";
+    "invalid pin attribute, currently only #[pin(input|output|io,latch)] is supported, along with an optional name = \"<pin name>\"";
 
-const INVALID_LATCH_ERR:&str = "invalid pin attribute, expected a latch pin name for io pin type : #[pin(io,<latch_pin_name>)]";
+const INVALID_BUS_ERR: &str =
+    "invalid bus attribute, expected #[bus] on a pin of array type with literal length, such as [bool; 8]";
 
+const UNNAMED_BUS_ERR: &str =
+    "bus pins of tuple fields must be given a name, such as #[pin(output, name = \"addr\")]";
+
+const INVALID_LATCH_ERR:&str = "invalid pin attribute, expected a latch pin name for io pin type : #[pin(io,<latch_pin_name>)], or the index of the latch for tuple fields";
+
+const PIN_NAME_ARG: &str = "name";
 const PIN_TYPE_INPUT: &str = "input";
 const PIN_TYPE_OUTPUT: &str = "output";
 const PIN_TYPE_IO: &str = "io";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum __PinType {
     Input,
     Output,
-    IO(syn::Ident),
+    IO(syn::Member),
 }
 
 impl std::fmt::Display for __PinType {
@@ -35,19 +58,82 @@ impl std::fmt::Display for __PinType {
 
 #[derive(Debug)]
 struct __PinMetadata<'a> {
-    name: &'a syn::Ident,
+    name: String,
+    // field storing the pin, which is the field name, or the index for tuple fields
+    field: syn::Member,
+    // index of the pin in the array field, for the pins of a #[bus]
+    index: Option<usize>,
     pin_type: __PinType,
     data_type: &'a syn::Type,
+    // the variant having the field, for enum chips
+    variant: Option<&'a syn::Ident>,
+}
+
+impl __PinMetadata<'_> {
+    // gives the expression for the field (or array element) storing the pin value. For enums,
+    // this is the field bound as __pin when matching on the variant, see access_pin
+    fn value(&self) -> TokenStream {
+        let field = &self.field;
+        match (self.variant, self.index) {
+            (Some(_), Some(i)) => quote! {__pin[#i]},
+            (Some(_), None) => quote! {(*__pin)},
+            (None, Some(i)) => quote! {self.#field[#i]},
+            (None, None) => quote! {self.#field},
+        }
+    }
+
+    // gives the expression for the value of the latch of an io pin, same as value
+    fn latch_value(&self) -> TokenStream {
+        match (&self.pin_type, self.variant) {
+            (__PinType::IO(_), Some(_)) => quote! {*__pin},
+            (__PinType::IO(latch), None) => quote! {self.#latch},
+            _ => unreachable!(),
+        }
+    }
+}
+
+// gives the code for the pin at index i, where the body uses the value of the pin. For structs
+// that is simply the body, for enums this matches on each variant and binds the field of
+// the pin (or of its latch) as __pin, as the variants can have the pin in different fields
+fn access_pin(
+    variants: &[Vec<__PinMetadata>],
+    i: usize,
+    latch: bool,
+    body: impl Fn(&__PinMetadata) -> TokenStream,
+) -> TokenStream {
+    if variants[0][i].variant.is_none() {
+        return body(&variants[0][i]);
+    }
+    let arms = variants.iter().map(|pins| {
+        let p = &pins[i];
+        let variant = p.variant.unwrap();
+        let field = match &p.pin_type {
+            __PinType::IO(latch_field) if latch => latch_field,
+            _ => &p.field,
+        };
+        let value = body(p);
+        quote! {
+            Self::#variant{#field: __pin, ..} => #value
+        }
+    });
+    quote! {
+        match self{
+            #(#arms,)*
+        }
+    }
+}
+
+fn is_attr(attr: &syn::Attribute, name: &str) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == name
 }
 
 fn get_pin_attr(f: &syn::Field) -> &syn::Attribute {
     for attr in &f.attrs {
-        if attr.path.segments.len() == 1 && attr.path.segments[0].ident == PIN_ATTRIBUTE {
+        if is_attr(attr, PIN_ATTRIBUTE) {
             return attr;
         }
     }
-    // as we have already filtered fields to which have attributes, and
-    // fields which have attribute other than pin, will give compile-error from rust's side
+    // as we have already filtered fields to which have the pin attribute,
     // not finding the attribute is essentially unreachable
     unreachable!()
 }
@@ -60,6 +146,26 @@ where
     syn::Error::new_spanned(t, m).to_compile_error()
 }
 
+// gives the fields along with how they are accessed, which is the field name
+// for named fields, and the index for tuple fields
+fn get_members(fields: &syn::Fields) -> Vec<(syn::Member, &syn::Field)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => (syn::Member::Named(ident.clone()), f),
+            None => (syn::Member::Unnamed(syn::Index::from(i)), f),
+        })
+        .collect()
+}
+
+fn member_name(member: &syn::Member) -> String {
+    match member {
+        syn::Member::Named(ident) => ident.to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
 fn get_pin_type(
     nm: syn::NestedMeta,
     latch: Option<syn::NestedMeta>,
@@ -70,55 +176,238 @@ fn get_pin_type(
             match ptype.to_string().as_str() {
                 PIN_TYPE_INPUT => Ok(__PinType::Input),
                 PIN_TYPE_OUTPUT => Ok(__PinType::Output),
-                PIN_TYPE_IO => {
-                    if let Some(syn::NestedMeta::Meta(syn::Meta::Path(mut path))) = latch {
+                PIN_TYPE_IO => match latch {
+                    Some(syn::NestedMeta::Meta(syn::Meta::Path(mut path))) => {
                         if path.segments.len() != 1 {
                             return Err(get_compiler_error(path, INVALID_LATCH_ERR));
                         }
                         let t = path.segments.pop().unwrap().into_value();
-                        Ok(__PinType::IO(t.ident))
-                    } else {
-                        return Err(get_compiler_error(path, INVALID_LATCH_ERR));
+                        Ok(__PinType::IO(syn::Member::Named(t.ident)))
                     }
+                    // the latch of a pin in a tuple struct is given by its index
+                    Some(syn::NestedMeta::Lit(syn::Lit::Int(i))) => match i.base10_parse() {
+                        Ok(index) => Ok(__PinType::IO(syn::Member::Unnamed(syn::Index {
+                            index,
+                            span: i.span(),
+                        }))),
+                        Err(e) => Err(e.to_compile_error()),
+                    },
+                    _ => Err(get_compiler_error(path, INVALID_LATCH_ERR)),
+                },
+                _ => Err(get_compiler_error(ptype, INVALID_PIN_ATTR_ERR)),
+            }
+        }
+        meta => Err(get_compiler_error(meta, INVALID_PIN_ATTR_ERR)),
+    }
+}
+
+// parses #[pin(<pin type>, <latch>, name = "<pin name>")], where the latch is only given for io pins,
+// and the name is optional, which is the field name (or index for tuple fields) by default
+fn parse_pin_attr(attr: &syn::Attribute) -> Result<(__PinType, Option<String>), TokenStream> {
+    let args = match attr.parse_meta() {
+        Err(e) => return Err(e.to_compile_error()),
+        Ok(syn::Meta::List(args)) => args,
+        Ok(meta) => return Err(get_compiler_error(meta, INVALID_PIN_ATTR_ERR)),
+    };
+    let mut args = args.nested.into_iter();
+    let pin_type_attr = match args.next() {
+        Some(arg) => arg,
+        None => return Err(get_compiler_error(attr, INVALID_PIN_ATTR_ERR)),
+    };
+    let mut latch = None;
+    let mut name = None;
+    for arg in args {
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident(PIN_NAME_ARG) => {
+                match nv.lit {
+                    syn::Lit::Str(s) if name.is_none() => name = Some(s.value()),
+                    lit => return Err(get_compiler_error(lit, INVALID_PIN_ATTR_ERR)),
                 }
-                _ => return Err(get_compiler_error(ptype, INVALID_PIN_ATTR_ERR)),
             }
+            arg if latch.is_none() => latch = Some(arg),
+            arg => return Err(get_compiler_error(arg, INVALID_PIN_ATTR_ERR)),
         }
-        meta => return Err(get_compiler_error(meta, INVALID_PIN_ATTR_ERR)),
     }
+    Ok((get_pin_type(pin_type_attr, latch)?, name))
 }
 
-fn get_pin_metadata<'a>(fields: &'a [&syn::Field]) -> Result<Vec<__PinMetadata<'a>>, TokenStream> {
+// gives the length and element type of a #[bus] pin, which must be an array with a literal length
+fn get_bus_width(f: &syn::Field) -> Result<Option<(usize, &syn::Type)>, TokenStream> {
+    let attr = match f.attrs.iter().find(|attr| is_attr(attr, BUS_ATTRIBUTE)) {
+        None => return Ok(None),
+        Some(attr) => attr,
+    };
+    if !attr.tokens.is_empty() {
+        return Err(get_compiler_error(attr, INVALID_BUS_ERR));
+    }
+    match &f.ty {
+        syn::Type::Array(syn::TypeArray {
+            elem,
+            len:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }),
+            ..
+        }) => match len.base10_parse() {
+            Ok(len) => Ok(Some((len, &**elem))),
+            Err(e) => Err(e.to_compile_error()),
+        },
+        ty => Err(get_compiler_error(ty, INVALID_BUS_ERR)),
+    }
+}
+
+// gives the fields marked as pins, #[bus] is not allowed on other fields
+fn get_pin_fields<'a>(
+    fields: &[(syn::Member, &'a syn::Field)],
+) -> Result<Vec<(syn::Member, &'a syn::Field)>, TokenStream> {
     let mut ret = Vec::with_capacity(fields.len());
-    for field in fields {
-        let pin_attr = get_pin_attr(field);
-        match pin_attr.parse_meta() {
-            Err(e) => return Err(e.to_compile_error()),
-            Ok(syn::Meta::List(mut args)) => {
-                let pin_type_attr;
-                let latch;
-                if args.nested.len() == 1 {
-                    latch = None;
-                    pin_type_attr = args.nested.pop().unwrap().into_value();
-                } else {
-                    // as pop removes in reverse order, first we get latch and then io
-                    latch = args.nested.pop().map(|s| s.into_value());
-                    pin_type_attr = args.nested.pop().unwrap().into_value();
+    for (member, field) in fields {
+        if field.attrs.iter().any(|attr| is_attr(attr, PIN_ATTRIBUTE)) {
+            ret.push((member.clone(), *field));
+        } else if let Some(attr) = field.attrs.iter().find(|attr| is_attr(attr, BUS_ATTRIBUTE)) {
+            return Err(get_compiler_error(attr, "#[bus] is only allowed on pins"));
+        }
+    }
+    Ok(ret)
+}
+
+fn get_pin_metadata<'a>(
+    fields: &[(syn::Member, &'a syn::Field)],
+    variant: Option<&'a syn::Ident>,
+) -> Result<Vec<__PinMetadata<'a>>, TokenStream> {
+    let mut ret: Vec<__PinMetadata> = Vec::with_capacity(fields.len());
+    for (member, field) in fields {
+        let (pin_type, name) = parse_pin_attr(get_pin_attr(field))?;
+        let start = ret.len();
+        match get_bus_width(field)? {
+            // each element of a bus is a separate pin, named as field name followed by
+            // its index, such as addr0, addr1 ... , with same pin type (and latch)
+            Some((width, elem)) => {
+                let name = match (name, member) {
+                    (Some(name), _) => name,
+                    (None, syn::Member::Named(ident)) => ident.to_string(),
+                    // names of the pins would be ambiguous for tuple fields, such as
+                    // 11 for the pin 1 of field 1 as well as the pin 11 of field 1
+                    (None, syn::Member::Unnamed(_)) => {
+                        return Err(get_compiler_error(field, UNNAMED_BUS_ERR))
+                    }
+                };
+                for i in 0..width {
+                    ret.push(__PinMetadata {
+                        name: format!("{}{}", name, i),
+                        field: member.clone(),
+                        index: Some(i),
+                        pin_type: pin_type.clone(),
+                        data_type: elem,
+                        variant,
+                    })
                 }
+            }
+            None => ret.push(__PinMetadata {
+                name: name.unwrap_or_else(|| member_name(member)),
+                field: member.clone(),
+                index: None,
+                pin_type,
+                data_type: &field.ty,
+                variant,
+            }),
+        }
+        // with the names given in the pin attributes, a name can be used for multiple pins,
+        // and the names of bus pins can be same as other pins, such as addr10 for both addr1[0] and addr[10]
+        for p in &ret[start..] {
+            if let Some(other) = ret[..start].iter().find(|other| other.name == p.name) {
+                let t = if p.index.is_some() || other.index.is_some() {
+                    format!(
+                        "multiple pins named {}, as {} and {}. The pins of a bus are named as the bus name followed by the index",
+                        p.name,
+                        describe_pin(other),
+                        describe_pin(p)
+                    )
+                } else {
+                    format!("multiple pins named {}", p.name)
+                };
+                return Err(get_compiler_error(field, t));
+            }
+        }
+    }
+    Ok(ret)
+}
 
-                let pin_type = get_pin_type(pin_type_attr, latch)?;
-                ret.push(__PinMetadata {
-                    name: &field.ident.as_ref().unwrap(),
-                    pin_type: pin_type,
-                    data_type: &field.ty,
-                })
+// describes the pin for errors, with its bus and index for the pins of buses
+fn describe_pin(p: &__PinMetadata) -> String {
+    match p.index {
+        Some(i) => {
+            let bus = &p.name[..p.name.len() - i.to_string().len()];
+            format!("pin {} of bus {}", i, bus)
+        }
+        None => format!("pin {}", p.name),
+    }
+}
+
+// the pins of all the variants of an enum chip must be same, as the chip has a single list of pins.
+// This orders the pins of each variant same as the first variant, so each pin has the same index
+fn match_variant_pins<'a>(
+    mut variants: Vec<(&'a syn::Variant, Vec<__PinMetadata<'a>>)>,
+) -> Result<Vec<Vec<__PinMetadata<'a>>>, TokenStream> {
+    let (first, first_pins) = variants.remove(0);
+    let mut ret = Vec::with_capacity(variants.len() + 1);
+    for (variant, mut pins) in variants {
+        let mut ordered = Vec::with_capacity(pins.len());
+        for p in &first_pins {
+            let pin = match pins.iter().position(|other| other.name == p.name) {
+                Some(i) => pins.remove(i),
+                None => {
+                    let t = format!(
+                        "variant {} does not have the pin {} of variant {}, all variants of a chip enum must have the same pins",
+                        variant.ident, p.name, first.ident
+                    );
+                    return Err(get_compiler_error(variant, t));
+                }
+            };
+            let (ty1, ty2) = (p.data_type, pin.data_type);
+            if quote! {#ty1}.to_string() != quote! {#ty2}.to_string()
+                || p.pin_type.to_string() != pin.pin_type.to_string()
+            {
+                let t = format!(
+                    "pin {} is {} {} in variant {}, but {} {} in variant {}",
+                    p.name,
+                    p.pin_type,
+                    quote! {#ty1},
+                    first.ident,
+                    pin.pin_type,
+                    quote! {#ty2},
+                    variant.ident
+                );
+                return Err(get_compiler_error(ty2, t));
             }
-            Ok(meta) => return Err(get_compiler_error(meta, INVALID_PIN_ATTR_ERR)),
+            ordered.push(pin);
+        }
+        if let Some(p) = pins.first() {
+            let t = format!(
+                "pin {} of variant {} is not present in variant {}, all variants of a chip enum must have the same pins",
+                p.name, variant.ident, first.ident
+            );
+            return Err(get_compiler_error(variant, t));
         }
+        ret.push(ordered);
     }
+    ret.insert(0, first_pins);
     Ok(ret)
 }
 
+// gives if the pin is of pcb_rs::Logic or pcb_rs::LogicVec type, which are tristatable
+// with Z as the tristated state. This is a soft check same as pin_is_tristatable
+fn pin_is_logic(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) if p.qself.is_none() => {
+            let last = p.path.segments.last().unwrap();
+            (last.ident == "Logic" && last.arguments.is_empty()) || last.ident == "LogicVec"
+        }
+        _ => false,
+    }
+}
+
 fn pin_is_tristatable(ty: &syn::Type) -> bool {
     // this is a soft check rather than a hard check if the pin is tristatable
     // or not. Technically users can define an `Option` struct/enum in their code
@@ -126,6 +415,9 @@ fn pin_is_tristatable(ty: &syn::Type) -> bool {
     // later in pcb! generated module to see if a pin can be tristatable or not.
     // In case one does use such custom enum, it will fail to compile due to the way
     // is_tristated fn is implemented in the Chip derive macro
+    if pin_is_logic(ty) {
+        return true;
+    }
     match ty {
         syn::Type::Path(p) => {
             let segments: Vec<_> = p.path.segments.iter().collect();
@@ -140,7 +432,7 @@ fn pin_is_tristatable(ty: &syn::Type) -> bool {
                 return segments[0].ident == "option" && segments[1].ident == "Option";
             }
             // if user it using the "normal" way
-            if segments.len() >= 1 {
+            if !segments.is_empty() {
                 return segments[0].ident == "Option";
             }
 
@@ -150,101 +442,522 @@ fn pin_is_tristatable(ty: &syn::Type) -> bool {
     }
 }
 
-pub fn derive_chip_impl(name: &syn::Ident, data: &syn::DataStruct) -> TokenStream {
-    let fields = match &data.fields {
-        syn::Fields::Unit | syn::Fields::Unnamed(_) => {
-            panic!("Chip derive is only supported for named field structs")
+// these are the types which implement pcb_rs::PinData, and thus have their own variant
+// in pcb_rs::PinValue, along with [bool; N] bit vectors and Option of all of these
+const PIN_DATA_TYPES: &[&str] = &[
+    "bool", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+fn pin_has_pin_data(ty: &syn::Type, allow_option: bool) -> bool {
+    // Similar to pin_is_tristatable, this is a soft check based on how the type is written.
+    // Any type for which this gives false is passed around boxed as PinValue::Custom, and
+    // in case a custom type shadows one of these names, the generated code will fail to compile
+    if pin_is_logic(ty) {
+        // Logic and LogicVec have their own variant, but Option of them does not
+        return allow_option;
+    }
+    match ty {
+        syn::Type::Array(arr) => {
+            matches!(&*arr.elem, syn::Type::Path(p) if p.path.is_ident("bool"))
+        }
+        syn::Type::Path(p) if p.qself.is_none() => {
+            if let Some(ident) = p.path.get_ident() {
+                return PIN_DATA_TYPES.contains(&ident.to_string().as_str());
+            }
+            if !allow_option || !pin_is_tristatable(ty) {
+                return false;
+            }
+            match &p.path.segments.last().unwrap().arguments {
+                syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                    match &args.args[0] {
+                        syn::GenericArgument::Type(inner) => pin_has_pin_data(inner, false),
+                        _ => false,
+                    }
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// gives the expression converting the value of the pin (or state) field to pcb_rs::PinValue.
+// The derive only sees the type parameter in the types using them, so those are converted through
+// PinData at runtime when the concrete type implements it, and are boxed as PinValue::Custom otherwise
+fn to_pin_value(value: &TokenStream, dtype: &syn::Type, params: &[&syn::Ident]) -> TokenStream {
+    if pin_has_pin_data(dtype, true) {
+        quote! {<#dtype as pcb_rs::PinData>::to_pin_value(&#value)}
+    } else if uses_type_params(quote! {#dtype}, params) {
+        quote! {pcb_rs::generic_to_pin_value::<#dtype>(&#value)}
+    } else {
+        quote! {pcb_rs::PinValue::Custom(std::boxed::Box::new(#value.clone()))}
+    }
+}
+
+// gives the expression converting `val`, a &pcb_rs::PinValue, to an Option of the pin data type.
+// The PinData types also accept their Custom values, in case of manually implemented chips giving those
+fn from_pin_value(dtype: &syn::Type, params: &[&syn::Ident]) -> TokenStream {
+    if pin_has_pin_data(dtype, true) {
+        quote! {<#dtype as pcb_rs::PinData>::from_pin_value(val).or_else(|| val.downcast_ref::<#dtype>().cloned())}
+    } else if uses_type_params(quote! {#dtype}, params) {
+        quote! {pcb_rs::generic_from_pin_value::<#dtype>(val)}
+    } else {
+        quote! {val.downcast_ref::<#dtype>().cloned()}
+    }
+}
+
+fn is_combinational(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| is_attr(attr, COMBINATIONAL_ATTRIBUTE))
+}
+
+// gives if the struct has #[reset], in which case we generate the reset fn
+fn has_reset(attrs: &[syn::Attribute]) -> Result<bool, TokenStream> {
+    match attrs.iter().find(|attr| is_attr(attr, RESET_ATTRIBUTE)) {
+        None => Ok(false),
+        Some(attr) if attr.tokens.is_empty() => Ok(true),
+        Some(attr) => Err(get_compiler_error(attr, INVALID_RESET_ERR)),
+    }
+}
+
+// gives the value given in #[reset = <literal>] or #[reset(<expr>)] on a field, if any.
+// rust only allows literals after =, so the second form is for any other values
+fn get_reset_value(f: &syn::Field) -> Result<Option<syn::Expr>, TokenStream> {
+    use syn::parse::Parser;
+    let attr = match f.attrs.iter().find(|attr| is_attr(attr, RESET_ATTRIBUTE)) {
+        None => return Ok(None),
+        Some(attr) => attr,
+    };
+    let parser = |input: syn::parse::ParseStream| {
+        if input.peek(syn::Token![=]) {
+            let _ = input.parse::<syn::Token![=]>()?;
+            input.parse::<syn::Expr>()
+        } else {
+            let content;
+            let _ = syn::parenthesized!(content in input);
+            content.parse::<syn::Expr>()
         }
-        syn::Fields::Named(f) => &f.named,
     };
+    match parser.parse2(attr.tokens.clone()) {
+        Ok(expr) => Ok(Some(expr)),
+        Err(_) => Err(get_compiler_error(attr, INVALID_RESET_ERR)),
+    }
+}
 
-    let pin_fields = {
-        let mut ret = Vec::with_capacity(fields.len());
-        for field in fields {
-            if field.attrs.len() != 0 {
-                ret.push(field);
+// the reset fn sets each field to the value given in its reset attribute, or to its default value
+fn get_reset_fn(
+    attrs: &[syn::Attribute],
+    fields: &[(syn::Member, &syn::Field)],
+) -> Result<TokenStream, TokenStream> {
+    let reset = has_reset(attrs)?;
+    let mut field_resets = Vec::with_capacity(fields.len());
+    for (member, field) in fields {
+        match get_reset_value(field)? {
+            Some(_) if !reset => {
+                let attr = field
+                    .attrs
+                    .iter()
+                    .find(|attr| is_attr(attr, RESET_ATTRIBUTE));
+                return Err(get_compiler_error(attr, RESET_NOT_ENABLED_ERR));
             }
+            Some(val) => field_resets.push(quote! {self.#member = #val;}),
+            None => field_resets.push(quote! {self.#member = std::default::Default::default();}),
+        }
+    }
+    if !reset {
+        return Ok(quote! {});
+    }
+    Ok(quote! {
+        fn reset(&mut self){
+            #(#field_resets)*
+        }
+    })
+}
+
+// for chips with #[state] fields, the saved state has those fields in its state section, by their
+// field names, which are converted to pcb_rs::PinValue same as the pins. The pins are saved by their
+// pin names same as the default implementation, which other chips use
+fn get_state_fns(
+    fields: &[(syn::Member, &syn::Field)],
+    params: &[&syn::Ident],
+) -> Result<TokenStream, TokenStream> {
+    let mut state_fields = Vec::new();
+    for (_, field) in fields {
+        if let Some(attr) = field.attrs.iter().find(|a| is_attr(a, STATE_ATTRIBUTE)) {
+            if !attr.tokens.is_empty() {
+                return Err(get_compiler_error(
+                    attr,
+                    "invalid state attribute, expected #[state]",
+                ));
+            }
+            state_fields.push(field);
+        }
+    }
+    if state_fields.is_empty() {
+        return Ok(quote! {});
+    }
+    // a field marked as both pin and state is saved once, along with the pins
+    let saved: Vec<_> = fields
+        .iter()
+        .filter(|(_, f)| {
+            f.attrs.iter().any(|a| is_attr(a, STATE_ATTRIBUTE))
+                && !f.attrs.iter().any(|a| is_attr(a, PIN_ATTRIBUTE))
+        })
+        .collect();
+
+    let save = saved.iter().map(|(member, f)| {
+        let name_string = member_name(member);
+        let value = to_pin_value(&quote! {self.#member}, &f.ty, params);
+        quote! {
+            state.state.insert(std::string::String::from(#name_string),#value);
+        }
+    });
+
+    let restore_match_arm = saved.iter().map(|(member, f)| {
+        let name_string = member_name(member);
+        let dtype = &f.ty;
+        let value = from_pin_value(dtype, params);
+        quote! {
+            #name_string => match #value{
+                std::option::Option::Some(v) => self.#member = v,
+                std::option::Option::None => return std::result::Result::Err(pcb_rs::StateError::InvalidValue{
+                    path: name.clone(),
+                    error: pcb_rs::PinValueError::TypeMismatch{
+                        pin: #name_string,
+                        expected: std::any::type_name::<#dtype>()
+                    }
+                })
+            }
+        }
+    });
+
+    Ok(quote! {
+        fn save_state(&self) -> pcb_rs::ChipState{
+            let mut state = pcb_rs::save_pins(self);
+            #(#save)*
+            state
+        }
+
+        fn restore_state(&mut self, state: &pcb_rs::ChipState) -> std::result::Result<(), pcb_rs::StateError>{
+            pcb_rs::restore_pins(self, state)?;
+            for (name,val) in &state.state{
+                match name.as_str(){
+                    #(#restore_match_arm,)*
+                    _ => return std::result::Result::Err(pcb_rs::StateError::InvalidValue{
+                        path: name.clone(),
+                        error: pcb_rs::PinValueError::UnknownPin(name.clone())
+                    })
+                }
+            }
+            std::result::Result::Ok(())
+        }
+    })
+}
+
+// gives if the type mentions any of the type parameters, such as T, Option<T> or [T; 8].
+// This is a soft check on the tokens of the type, which is enough to decide which types need bounds
+fn uses_type_params(ty: TokenStream, params: &[&syn::Ident]) -> bool {
+    ty.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(ident) => params.iter().any(|p| **p == ident),
+        proc_macro2::TokenTree::Group(g) => uses_type_params(g.stream(), params),
+        _ => false,
+    })
+}
+
+// for generic chips, the generated code needs the bounds on types of pins and other fields it uses,
+// in addition to the ones the user has given. The pins are cloned and boxed as pcb_rs::CustomValue when they
+// are not PinData, so their types must be Clone + pcb_rs::CustomPinData, the output pins of combinational chips are
+// compared, so they must be PartialEq, and fields reset to default values must be Default.
+// Only the types using type parameters get the bounds, as others are checked as they are.
+// Enums are reset to their default value, so for those self_default adds the bound on the enum
+fn get_generics(
+    generics: &syn::Generics,
+    attrs: &[syn::Attribute],
+    fields: &[(syn::Member, &syn::Field)],
+    metadata: &[__PinMetadata],
+    self_default: bool,
+) -> Result<syn::Generics, TokenStream> {
+    let params: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+    let mut generics = generics.clone();
+    if params.is_empty() {
+        return Ok(generics);
+    }
+    let mut bounds: Vec<(&syn::Type, TokenStream)> = Vec::new();
+    let clone_bound = quote! {std::clone::Clone + pcb_rs::CustomPinData};
+    for p in metadata {
+        bounds.push((p.data_type, clone_bound.clone()));
+        if is_combinational(attrs) && !matches!(p.pin_type, __PinType::Input) {
+            bounds.push((p.data_type, quote! {std::cmp::PartialEq}));
+        }
+    }
+    let reset = has_reset(attrs)?;
+    for (_, field) in fields {
+        if field.attrs.iter().any(|a| is_attr(a, STATE_ATTRIBUTE)) {
+            bounds.push((&field.ty, clone_bound.clone()));
+        }
+        if reset && get_reset_value(field)?.is_none() {
+            bounds.push((&field.ty, quote! {std::default::Default}));
+        }
+    }
+    let where_clause = generics.make_where_clause();
+    for (ty, bound) in bounds {
+        if uses_type_params(quote! {#ty}, &params) {
+            where_clause
+                .predicates
+                .push(syn::parse_quote! {#ty: #bound});
+        }
+    }
+    if self_default {
+        where_clause
+            .predicates
+            .push(syn::parse_quote! {Self: std::default::Default});
+    }
+    Ok(generics)
+}
+
+// fields of enums are reset along with the whole enum and are not saved in the state,
+// so those attributes are not allowed on them
+fn check_variant_fields(fields: &[(syn::Member, &syn::Field)]) -> Result<(), TokenStream> {
+    for (_, field) in fields {
+        for attr in &field.attrs {
+            if is_attr(attr, RESET_ATTRIBUTE) {
+                return Err(get_compiler_error(attr, ENUM_RESET_ERR));
+            }
+            if is_attr(attr, STATE_ATTRIBUTE) {
+                return Err(get_compiler_error(attr, ENUM_STATE_ERR));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn derive_chip_impl(
+    name: &syn::Ident,
+    attrs: &[syn::Attribute],
+    generics: &syn::Generics,
+    data: &syn::Data,
+) -> TokenStream {
+    match derive_chip(name, attrs, generics, data) {
+        Ok(t) | Err(t) => t,
+    }
+}
+
+fn derive_chip(
+    name: &syn::Ident,
+    attrs: &[syn::Attribute],
+    generics: &syn::Generics,
+    data: &syn::Data,
+) -> Result<TokenStream, TokenStream> {
+    let params: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+    // for structs there is a single list of pins, and for enums a list for each variant,
+    // where the pins of all variants are same, and at same indices
+    let (variants, reset_fn, state_fns, generics) = match data {
+        syn::Data::Struct(chip_struct) => {
+            let fields = get_members(&chip_struct.fields);
+            let metadata = get_pin_metadata(&get_pin_fields(&fields)?, None)?;
+            let reset_fn = get_reset_fn(attrs, &fields)?;
+            let state_fns = get_state_fns(&fields, &params)?;
+            let generics = get_generics(generics, attrs, &fields, &metadata, false)?;
+            (vec![metadata], reset_fn, state_fns, generics)
+        }
+        syn::Data::Enum(chip_enum) => {
+            let mut variants = Vec::with_capacity(chip_enum.variants.len());
+            for variant in &chip_enum.variants {
+                let fields = get_members(&variant.fields);
+                check_variant_fields(&fields)?;
+                let metadata = get_pin_metadata(&get_pin_fields(&fields)?, Some(&variant.ident))?;
+                variants.push((variant, metadata));
+            }
+            if variants.is_empty() {
+                return Err(get_compiler_error(
+                    name,
+                    "Chip derive is not supported for enums without variants",
+                ));
+            }
+            let variants = match_variant_pins(variants)?;
+            let reset = has_reset(attrs)?;
+            let reset_fn = if reset {
+                quote! {
+                    fn reset(&mut self){
+                        *self = std::default::Default::default();
+                    }
+                }
+            } else {
+                quote! {}
+            };
+            let generics = get_generics(generics, attrs, &[], &variants[0], reset)?;
+            (variants, reset_fn, quote! {}, generics)
+        }
+        syn::Data::Union(_) => {
+            return Err(get_compiler_error(
+                name,
+                "Chip derive is only supported for structs and enums",
+            ))
         }
-        ret
-    };
-    let metadata = match get_pin_metadata(&pin_fields) {
-        Result::Ok(md) => md,
-        Result::Err(e) => return e,
     };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let metadata = &variants[0];
 
     let pin_hashmap_arm = metadata.iter().map(|p| {
-        let name = p.name.to_string();
-        let ptype = syn::Ident::new(&p.pin_type.to_string(), data.struct_token.span);
+        let name = &p.name;
+        let ptype = syn::Ident::new(&p.pin_type.to_string(), proc_macro2::Span::call_site());
         // have to do that, as we can't access it as #p.data_type
-        let __temp = p.data_type;
-        let dtype = quote! {#__temp}.to_string();
+        let dtype = p.data_type;
 
-        let tristatable = pin_is_tristatable(__temp);
+        let tristatable = pin_is_tristatable(dtype);
 
         quote! {
             #name, pcb_rs::PinMetadata{
                 pin_type:pcb_rs::PinType::#ptype,
-                data_type:#dtype,
+                data_type:std::any::type_name::<#dtype>(),
+                type_id:std::any::TypeId::of::<#dtype>(),
                 tristatable:#tristatable
             }
         }
     });
 
-    let get_pin_match_arm = metadata.iter().map(|p| {
-        let name = p.name;
-        let name_string = p.name.to_string();
+    // same as the pin list, for checking the pins when compiling, see pcb_rs::ChipPins
+    let pin_descriptors = metadata.iter().map(|p| {
+        let name = &p.name;
+        let ptype = syn::Ident::new(&p.pin_type.to_string(), proc_macro2::Span::call_site());
+        let tristatable = pin_is_tristatable(p.data_type);
         quote! {
-            #name_string => std::option::Option::Some(std::boxed::Box::new(self.#name.clone()))
+            pcb_rs::PinDescriptor{
+                name:#name,
+                pin_type:pcb_rs::PinType::#ptype,
+                tristatable:#tristatable
+            }
+        }
+    });
 
+    // the pins are indexed in the order of declaration, and the *_at fns match on that index,
+    // the fns taking pin names get the index using get_pin_index and call those
+    let pin_index_match_arm = metadata.iter().enumerate().map(|(i, p)| {
+        let name_string = &p.name;
+        quote! {
+            #name_string => std::option::Option::Some(#i)
         }
     });
 
-    let set_pin_match_arm = metadata.iter().map(|p| {
-        let __name = p.name;
-        let name_string = p.name.to_string();
-        let dtype = p.data_type;
-        let assertion_err_msg = format!("internal error in pcb_rs derive chip : value sent to chip {} pin {} is of incorrect type",name,__name);
+    let get_pin_match_arm = (0..metadata.len()).map(|i| {
+        let value = access_pin(&variants, i, false, |p| {
+            to_pin_value(&p.value(), p.data_type, &params)
+        });
         quote! {
-            #name_string =>{
-                assert!(val.is::<#dtype>(), #assertion_err_msg);
-                self.#__name = val.downcast_ref::<#dtype>().unwrap().clone();
+            #i => std::option::Option::Some(#value)
+        }
+    });
+
+    let set_pin_match_arm = (0..metadata.len()).map(|i| {
+        let set = access_pin(&variants, i, false, |p| {
+            let field = p.value();
+            let name_string = &p.name;
+            let dtype = p.data_type;
+            let value = from_pin_value(p.data_type, &params);
+            quote! {
+                match #value{
+                    std::option::Option::Some(v) => {
+                        #field = v;
+                        std::result::Result::Ok(())
+                    }
+                    std::option::Option::None => std::result::Result::Err(pcb_rs::PinValueError::TypeMismatch{
+                        pin: #name_string,
+                        expected: std::any::type_name::<#dtype>()
+                    })
+                }
             }
+        });
+        quote! {
+            #i => #set
         }
     });
 
-    let tristated_match_arm = metadata.iter().map(|p| {
-        let name = p.name;
-        let name_string = p.name.to_string();
+    let tristated_match_arm = metadata.iter().enumerate().map(|(i, p)| {
         let dtype = p.data_type;
 
         // This is the hard check of tristatability. In case the user tries to use some custom type also
         // named `Option`, then they will get an compile time error, as the match arms are incompatible
-        if pin_is_tristatable(dtype) {
+        if pin_is_logic(dtype) {
+            let tristated = access_pin(&variants, i, false, |p| {
+                let field = p.value();
+                quote! {#field.is_z()}
+            });
             quote! {
-                #name_string => matches!(self.#name,std::option::Option::None)
+                #i => #tristated
+            }
+        } else if pin_is_tristatable(dtype) {
+            let tristated = access_pin(&variants, i, false, |p| {
+                let field = p.value();
+                quote! {matches!(#field,std::option::Option::None)}
+            });
+            quote! {
+                #i => #tristated
             }
         } else {
-            quote! {#name_string => false}
+            quote! {#i => false}
         }
     });
 
     let latch_match_arm = metadata
         .iter()
-        .filter(|s| matches!(s.pin_type, __PinType::IO(_)))
-        .map(|p| {
-            let name = p.name;
-            let name_string = name.to_string();
-            let latch_pin = match &p.pin_type {
-                __PinType::IO(pin) => pin,
-                _ => unreachable!(),
-            };
+        .enumerate()
+        .filter(|(_, s)| matches!(s.pin_type, __PinType::IO(_)))
+        .map(|(i, _)| {
+            let latch = access_pin(&variants, i, true, |p| p.latch_value());
             quote! {
-                #name_string => self.#latch_pin
+                #i => #latch
             }
         });
 
-    quote! {
-        impl pcb_rs::ChipInterface for #name{
+    // for combinational chips, the encompassing pcb needs to know when the outputs have
+    // stopped changing, so we compare the output (and io) pins, which requires them to be PartialEq.
+    // For non-combinational chips we do not generate these, so their pins are not required to be PartialEq
+    let combinational_fns = if is_combinational(attrs) {
+        let pin_eq_match_arm = metadata
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !matches!(p.pin_type, __PinType::Input))
+            .map(|(i, _)| {
+                let eq = access_pin(&variants, i, false, |p| {
+                    let field = p.value();
+                    let value = from_pin_value(p.data_type, &params);
+                    quote! {
+                        match #value{
+                            std::option::Option::Some(v) => #field == v,
+                            std::option::Option::None => false
+                        }
+                    }
+                });
+                quote! {
+                    #i => #eq
+                }
+            });
+        quote! {
+            fn is_combinational(&self) -> bool{
+                true
+            }
+
+            fn pin_value_eq(&self, name: &str, val: &pcb_rs::PinValue) -> bool{
+                match self.get_pin_index(name){
+                    std::option::Option::Some(i) => self.pin_value_eq_at(i,val),
+                    std::option::Option::None => false
+                }
+            }
+
+            fn pin_value_eq_at(&self, index: usize, val: &pcb_rs::PinValue) -> bool{
+                match index{
+                    #(#pin_eq_match_arm,)*
+                    _ => false
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #impl_generics pcb_rs::ChipInterface for #name #ty_generics #where_clause{
 
             fn get_pin_list(&self) -> std::collections::HashMap<&'static str, pcb_rs::PinMetadata>{
                 use std::collections::HashMap;
@@ -254,37 +967,75 @@ pub fn derive_chip_impl(name: &syn::Ident, data: &syn::DataStruct) -> TokenStrea
             }
 
 
-            fn get_pin_value(&self,name: &str) -> std::option::Option<Box<dyn std::any::Any>>{
-                use std::any::Any;
-                use std::boxed::Box;
+            fn get_pin_value(&self,name: &str) -> std::option::Option<pcb_rs::PinValue>{
+                self.get_pin_value_at(self.get_pin_index(name)?)
+            }
+
+            fn set_pin_value(&mut self,name: &str, val: &pcb_rs::PinValue) -> std::result::Result<(), pcb_rs::PinValueError>{
+                match self.get_pin_index(name){
+                    std::option::Option::Some(i) => self.set_pin_value_at(i,val),
+                    std::option::Option::None => std::result::Result::Err(pcb_rs::PinValueError::UnknownPin(name.to_string()))
+                }
+            }
+
+            fn is_pin_tristated(&self,name:&str)->bool{
+                match self.get_pin_index(name){
+                    std::option::Option::Some(i) => self.is_pin_tristated_at(i),
+                    std::option::Option::None => false
+                }
+            }
+
+            fn in_input_mode(&self,name:&str)->bool{
+                match self.get_pin_index(name){
+                    std::option::Option::Some(i) => self.in_input_mode_at(i),
+                    std::option::Option::None => false
+                }
+            }
+
+            fn get_pin_index(&self,name: &str) -> std::option::Option<usize>{
                 match name{
-                    #(#get_pin_match_arm,)*
+                    #(#pin_index_match_arm,)*
                     _ => std::option::Option::None
                 }
             }
 
+            fn get_pin_value_at(&self,index: usize) -> std::option::Option<pcb_rs::PinValue>{
+                match index{
+                    #(#get_pin_match_arm,)*
+                    _ => std::option::Option::None
+                }
+            }
 
-            fn set_pin_value(&mut self,name: &str, val: &dyn std::any::Any){
-                use std::any::Any;
-                match name{
+            fn set_pin_value_at(&mut self,index: usize, val: &pcb_rs::PinValue) -> std::result::Result<(), pcb_rs::PinValueError>{
+                match index{
                     #(#set_pin_match_arm,)*
-                    _ => {}
+                    _ => std::result::Result::Err(pcb_rs::PinValueError::UnknownPin(index.to_string()))
                 }
             }
 
-            fn is_pin_tristated(&self,name:&str)->bool{
-                match name{
+            fn is_pin_tristated_at(&self,index: usize)->bool{
+                match index{
                     #(#tristated_match_arm,)*
-                    _ => {false}
+                    _ => false
                 }
             }
 
-            fn in_input_mode(&self,name:&str)->bool{
-                match name{
+            fn in_input_mode_at(&self,index: usize)->bool{
+                match index{
                     #(#latch_match_arm,)*
                     _ => false
                 }
             }
+
+            #combinational_fns
+
+            #reset_fn
+
+            #state_fns
         }
-    }
+
+        impl #impl_generics pcb_rs::ChipPins for #name #ty_generics #where_clause{
+            const PINS: &'static [pcb_rs::PinDescriptor] = &[#(#pin_descriptors),*];
+        }
+    })
 }
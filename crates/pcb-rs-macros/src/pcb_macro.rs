@@ -1,31 +1,198 @@
-use quote::quote;
+use quote::{quote, quote_spanned};
 use std::collections::{HashMap, HashSet};
 use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
 use syn::{Result, Token};
 // ! TODO Add better error reporting
 // ! TODO maybe refactor the pin validation fn, where it also sets the pin metadata?
 
 const CHIP_DEFINITION_KEYWORD: &str = "chip";
 const PIN_EXPOSE_KEYWORD: &str = "expose";
-
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+const CHIP_PRIORITY_KEYWORD: &str = "priority";
+const CONTENTION_KEYWORD: &str = "contention";
+const PULL_KEYWORD: &str = "pull";
+const KEEP_KEYWORD: &str = "keep";
+const CLOCK_KEYWORD: &str = "clock";
+const CLOCK_PHASE_KEYWORD: &str = "phase";
+const MAIN_CLOCK: &str = "main";
+
+// typed chips get methods of their names in the builder and the pcb, so they cannot
+// use the names of other methods of those, including the _mut accessors. The pcb implements
+// HardwareModule, so the methods of its traits are included, as inherent methods shadow those
+const RESERVED_CHIP_NAMES: &[&str] = &[
+    // builder
+    "new", "add_chip", "contention_policy", "bus_contention_policy", "pull", "build",
+    "check_chip_types",
+    // pcb
+    "get_chip", "take_contentions",
+    // ChipInterface
+    "get_pin_list", "get_pin_value", "set_pin_value", "is_pin_tristated", "in_input_mode",
+    "is_combinational", "pin_value_eq", "get_pin_index", "get_pin_value_at", "set_pin_value_at",
+    "is_pin_tristated_at", "in_input_mode_at", "pin_value_eq_at", "reset", "save_state",
+    "restore_state", "get_chips", "get_pin_connections", "to_dot",
+    // Chip
+    "tick", "try_tick", "try_tick_edge", "uses_falling_edge",
+    // Downcast
+    "into_any", "into_any_rc", "as_any",
+];
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct __ChipPin {
     chip: String,
     pin: String,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 struct __ExposedPins{
     pins:Vec<__ChipPin>,
+    // spans of the pins in the expose statement, for the compile time checks of typed chips
+    spans:Vec<proc_macro2::Span>,
     as_name:String
 }
 
+// gives the pcb_rs::ContentionPolicy variant for the policy name used in `contention` statements
+fn parse_contention_policy(input: ParseStream) -> Result<proc_macro2::TokenStream> {
+    let policy = syn::Ident::parse(input)?;
+    let variant = match policy.to_string().as_str() {
+        "panic" => quote!{Panic},
+        "error" => quote!{Error},
+        "unknown" => quote!{Unknown},
+        "wired_and" => quote!{WiredAnd},
+        "wired_or" => quote!{WiredOr},
+        _ => {
+            let t = format!("unknown contention policy {}, expected one of panic, error, unknown, wired_and or wired_or", policy);
+            return Err(syn::Error::new_spanned(policy,t));
+        }
+    };
+    Ok(quote!{pcb_rs::ContentionPolicy::#variant})
+}
+
+// values of the variables of the enclosing for loops, which can be used in the indices
+type LoopVars = HashMap<String, i64>;
+
+// parses a single value in an index, which is an integer, a loop variable or an index in parentheses
+fn parse_index_atom(input: ParseStream, vars: &LoopVars) -> Result<i64> {
+    if input.peek(syn::token::Paren) {
+        let content;
+        let _ = syn::parenthesized!(content in input);
+        return parse_index(&content, vars);
+    }
+    if input.peek(syn::Ident) {
+        let var = syn::Ident::parse(input)?;
+        return match vars.get(&var.to_string()) {
+            Some(v) => Ok(*v),
+            None => {
+                let t = format!("unknown loop variable {} in index", var);
+                Err(syn::Error::new_spanned(var, t))
+            }
+        };
+    }
+    syn::LitInt::parse(input)?.base10_parse()
+}
+
+// parses an index such as `3`, `i` or `2*i+1`, where the identifiers are the variables
+// of the enclosing for loops, giving its value
+fn parse_index(input: ParseStream, vars: &LoopVars) -> Result<i64> {
+    let mut value = parse_index_term(input, vars)?;
+    loop {
+        if input.peek(Token![+]) {
+            let _ = <Token![+]>::parse(input)?;
+            value += parse_index_term(input, vars)?;
+        } else if input.peek(Token![-]) {
+            let _ = <Token![-]>::parse(input)?;
+            value -= parse_index_term(input, vars)?;
+        } else {
+            return Ok(value);
+        }
+    }
+}
+
+fn parse_index_term(input: ParseStream, vars: &LoopVars) -> Result<i64> {
+    let mut value = parse_index_atom(input, vars)?;
+    while input.peek(Token![*]) {
+        let _ = <Token![*]>::parse(input)?;
+        value *= parse_index_atom(input, vars)?;
+    }
+    Ok(value)
+}
+
+// parses `start..end` or `start..=end`, giving the indices in the range
+fn parse_index_range(input: ParseStream, vars: &LoopVars) -> Result<std::ops::Range<i64>> {
+    let start = parse_index(input, vars)?;
+    if input.peek(Token![..=]) {
+        let _ = <Token![..=]>::parse(input)?;
+        Ok(start..parse_index(input, vars)? + 1)
+    } else {
+        let _ = <Token![..]>::parse(input)?;
+        Ok(start..parse_index(input, vars)?)
+    }
+}
+
+// the pins of buses and the chips of chip arrays are named as the name followed
+// by the index, such as addr0 for addr[0], so they can be used as any other pin or chip
+fn indexed_name<T: quote::ToTokens>(name: &T, index: i64) -> Result<String> {
+    if index < 0 {
+        let t = format!("negative index {} for {}", index, quote!(#name));
+        return Err(syn::Error::new_spanned(name, t));
+    }
+    Ok(format!("{}{}", quote!(#name), index))
+}
+
+// pins are named by identifiers, or by their index for the unnamed pins of tuple struct chips, such as `c1::0`
+fn pin_name(pin: &syn::Member) -> String {
+    match pin {
+        syn::Member::Named(ident) => ident.to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+// parses the optional `[start..end]` or `[index]` after a pin name, and gives the names of the
+// individual pins of the bus, which are the pin name followed by the index, such as
+// `addr0, addr1` for `addr[0..2]`. If there is no range, only the pin name is given
+fn parse_pin_range<T: quote::ToTokens>(input: ParseStream, pin: &T, vars: &LoopVars) -> Result<Vec<String>> {
+    if !input.peek(syn::token::Bracket) {
+        return Ok(vec![quote!(#pin).to_string()]);
+    }
+    let content;
+    let brackets = syn::bracketed!(content in input);
+    let start = parse_index(&content, vars)?;
+    if content.is_empty() {
+        return Ok(vec![indexed_name(pin, start)?]);
+    }
+    let _ = <Token![..]>::parse(&content)?;
+    let end = parse_index(&content, vars)?;
+    if start >= end {
+        let t = format!("empty bus range {}..{} for pin {}", start, end, quote!(#pin));
+        return Err(syn::Error::new(brackets.span, t));
+    }
+    (start..end).map(|i| indexed_name(pin, i)).collect()
+}
+
 #[derive(Debug)]
 pub struct PcbMacroInput {
     name: syn::Ident,
     chip_map: HashMap<String, Vec<String>>,
+    // chip names in order of declaration, along with their tick priority
+    chip_priorities: Vec<(String, i32)>,
+    // number of chips in each chip array, such as `chip fa[8];`
+    chip_arrays: HashMap<String, usize>,
+    // chips declared with a type, such as `chip c1: Chip1;`, in order of declaration
+    chip_types: Vec<(String, syn::Type)>,
+    // declared clocks as (divider, phase) of the main clock, and the clocks of chips declared
+    // with one, such as `chip uart @ slow;`. Chips not in chip_clocks are of the main clock
+    clocks: HashMap<String, (u64, u64)>,
+    chip_clocks: HashMap<String, (u64, u64)>,
     pin_connection_list: HashMap<__ChipPin, HashSet<__ChipPin>>,
+    // each use of the pins and each connection, along with the spans of pins in the statements,
+    // which are used to point at the statements when the pins of typed chips are checked
+    pin_spans: Vec<(__ChipPin, proc_macro2::Span)>,
+    connection_spans: Vec<(__ChipPin, __ChipPin, proc_macro2::Span)>,
     exposed_pins: Vec<__ExposedPins>,
+    // policy for all the tristated groups, and for the groups of specific pins, as ContentionPolicy variants
+    contention_policy: Option<proc_macro2::TokenStream>,
+    contention_policies: Vec<(__ChipPin, proc_macro2::TokenStream)>,
+    // pulls of the groups of specific pins, as pcb_rs::Pull values
+    pulls: Vec<(__ChipPin, proc_macro2::TokenStream)>,
 }
 
 impl Parse for PcbMacroInput {
@@ -33,193 +200,479 @@ impl Parse for PcbMacroInput {
         let name = syn::Ident::parse(input)?;
         let content;
         let _braces = syn::braced!(content in input);
-        let mut kw;
-        let mut chip_map: HashMap<String, Vec<String>> = HashMap::new();
-
-        // this just stores a simple representation of connected pins,
-        // we convert this into a better structure to store into the builder in the into function
-        let mut pin_connection_list: HashMap<__ChipPin, HashSet<__ChipPin>> = HashMap::new();
 
-        let mut exposed_pins: Vec<__ExposedPins> = Vec::new();
+        let mut ret = PcbMacroInput {
+            name,
+            chip_map: HashMap::new(),
+            chip_priorities: Vec::new(),
+            chip_arrays: HashMap::new(),
+            chip_types: Vec::new(),
+            clocks: HashMap::new(),
+            chip_clocks: HashMap::new(),
+            // this just stores a simple representation of connected pins,
+            // we convert this into a better structure to store into the builder in the into function
+            pin_connection_list: HashMap::new(),
+            pin_spans: Vec::new(),
+            connection_spans: Vec::new(),
+            exposed_pins: Vec::new(),
+            contention_policy: None,
+            contention_policies: Vec::new(),
+            pulls: Vec::new(),
+        };
 
-        // this parses the module
+        // this parses the module, clocks are declared along with the chips, before the chips using them
         loop {
-            kw = syn::Ident::parse(&content)?;
-            if kw != CHIP_DEFINITION_KEYWORD {
+            let kw = content.fork().parse::<syn::Ident>();
+            if kw.as_ref().is_ok_and(|kw| kw == CHIP_DEFINITION_KEYWORD) {
+                ret.parse_chip(&content)?;
+            } else if kw.is_ok_and(|kw| kw == CLOCK_KEYWORD) {
+                ret.parse_clock(&content)?;
+            } else {
                 break;
             }
-            let module_name = syn::Ident::parse(&content)?;
-            let _ = <Token![;]>::parse(&content)?;
-            chip_map.insert(module_name.to_string(), Vec::new());
         }
 
-        if chip_map.is_empty() {
-           return Err(syn::Error::new_spanned(&name,"cannot make pcb with no chips!"));
+        if ret.chip_map.is_empty() {
+           return Err(syn::Error::new_spanned(&ret.name,"cannot make pcb with no chips!"));
             
         }
 
         // we allow pcb with no connections as one might use pcb as a
         // convenient collection of chips
+        ret.parse_statements(&content, &LoopVars::new())?;
 
-        // here the kw will actually point to name of chip, for pin connections
-        // unless there are no connections, in which case it will break on first iter
-        loop {
-            if kw == PIN_EXPOSE_KEYWORD {
-                break;
+        let mut as_names = HashSet::new();
+        for ep in &ret.exposed_pins{
+            if !as_names.insert(&ep.as_name){
+                let t = format!("multiple pins exposed as {} : pins to be exposed as the same pin must be given together, which shorts them",ep.as_name);
+                return Err(syn::Error::new(ep.spans[0],t));
             }
-            let chip1 = kw.to_string();
-            let _ = <Token![::]>::parse(&content)?;
-            let pin1 = syn::Ident::parse(&content)?.to_string();
-            // pin connection token is -
-            let _ = <Token![-]>::parse(&content);
-            let chip2 = syn::Ident::parse(&content)?.to_string();
-            let _ = <Token![::]>::parse(&content)?;
-            let pin2 = syn::Ident::parse(&content)?.to_string();
-            let _ = <Token![;]>::parse(&content)?;
+        }
 
-            if (&chip1,&pin1) == (&chip2,&pin2){
-                let t = format!("attempted to connect a pin to itself : chip `{}` pin `{}` appears to have a self-connection, which is redundant",chip1,pin1);
-                return Err(syn::Error::new_spanned(&chip1,t));
+        let mut temp = HashMap::new();
+        for ep in &ret.exposed_pins{
+            for p in &ep.pins{
+                if temp.contains_key(p){
+                    let previous = temp.get(p).unwrap();
+                    let t = format!("pin exposed multiple times : chip {} pin {} is exposed as {} and {}",
+                        p.chip,p.pin,previous, ep.as_name
+                    );
+                    return Err(syn::Error::new_spanned(&ret.name,t));
+                }else{
+                    temp.insert(p,&ep.as_name);
+                }
+                    
+                
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+impl From<PcbMacroInput> for proc_macro2::TokenStream {
+    fn from(val: PcbMacroInput) -> Self {
+        val.generate()
+    }
+}
+
+impl PcbMacroInput {
+
+    // parses `chip c1;`, along with the optional array length, type and priority, as in `chip fa[8]: FullAdder priority 2;`
+    fn parse_chip(&mut self, content: ParseStream) -> Result<()> {
+        let _ = syn::Ident::parse(content)?;
+        let module_name = syn::Ident::parse(content)?;
+        // chip arrays declare one chip per index, named fa0, fa1 ... for `chip fa[8];`
+        let is_array = content.peek(syn::token::Bracket);
+        let module_names = if is_array {
+            let count;
+            let _ = syn::bracketed!(count in content);
+            let len: usize = syn::LitInt::parse(&count)?.base10_parse()?;
+            if len == 0 {
+                let t = format!("chip array {} must have at least one chip", module_name);
+                return Err(syn::Error::new_spanned(module_name,t));
+            }
+            self.chip_arrays.insert(module_name.to_string(), len);
+            (0..len).map(|i| format!("{}{}", module_name, i)).collect()
+        } else {
+            vec![module_name.to_string()]
+        };
+        // chips can optionally have a type like `chip c1: Chip1;`, which gives typed methods for them
+        let chip_type = if content.peek(Token![:]) {
+            let _ = <Token![:]>::parse(content)?;
+            Some(syn::Type::parse(content)?)
+        } else {
+            None
+        };
+        // chips can optionally have a clock like `chip c1 @ slow;`, otherwise they are of the main clock
+        let clock = if content.peek(Token![@]) {
+            let _ = <Token![@]>::parse(content)?;
+            Some(self.parse_clock_name(content)?)
+        } else {
+            None
+        };
+        // chips can optionally have a priority like `chip c1 priority 2;`
+        let mut priority = 0;
+        if content.peek(syn::Ident) {
+            let kw = syn::Ident::parse(content)?;
+            if kw != CHIP_PRIORITY_KEYWORD {
+                let t = format!("expected `@`, `priority` or `;` after chip name, found {}", kw);
+                return Err(syn::Error::new_spanned(kw,t));
+            }
+            let negative = content.parse::<Option<Token![-]>>()?.is_some();
+            let value: i32 = syn::LitInt::parse(content)?.base10_parse()?;
+            priority = if negative { -value } else { value };
+        }
+        let _ = <Token![;]>::parse(content)?;
+        for name in module_names {
+            if self.chip_map.contains_key(&name) {
+                // the chips of chip arrays are named as the array name followed by the index,
+                // so those can be same as other chips, such as reg10 for both reg1[0] and reg[10]
+                let declared_by = self.chip_arrays.iter()
+                    .find(|(array,len)| module_name != array.as_str() && (0..**len).any(|i| format!("{}{}",array,i) == name));
+                let t = match (is_array,declared_by){
+                    (true,_) => format!("chip {} of chip array {} is already declared. The chips of a chip array are named as the array name followed by the index",name,module_name),
+                    (false,Some((array,_))) => format!("chip {} is already declared by chip array {}. The chips of a chip array are named as the array name followed by the index",name,array),
+                    (false,None) => format!("chip {} declared multiple times", name),
+                };
+                return Err(syn::Error::new_spanned(module_name,t));
+            }
+            if let Some(ty) = &chip_type {
+                if RESERVED_CHIP_NAMES.iter().any(|r| name == *r || name == format!("{}_mut", r)) {
+                    let t = format!("chip {} cannot be declared with a type, as the generated builder or pcb already has a method of that name", name);
+                    return Err(syn::Error::new_spanned(module_name,t));
+                }
+                self.chip_types.push((name.clone(), ty.clone()));
+            }
+            if let Some(clock) = clock {
+                self.chip_clocks.insert(name.clone(), clock);
+            }
+            self.chip_map.insert(name.clone(), Vec::new());
+            self.chip_priorities.push((name, priority));
+        }
+        Ok(())
+    }
+
+    // parses `clock slow = main / 4;`, along with the optional phase, as in `clock slower = slow / 2 phase 1;`
+    fn parse_clock(&mut self, content: ParseStream) -> Result<()> {
+        let _ = syn::Ident::parse(content)?;
+        let name = syn::Ident::parse(content)?;
+        if name == MAIN_CLOCK || self.clocks.contains_key(&name.to_string()) {
+            let t = format!("clock {} declared multiple times, the main clock is declared implicitly", name);
+            return Err(syn::Error::new_spanned(name,t));
+        }
+        let _ = <Token![=]>::parse(content)?;
+        let (parent_divider, parent_phase) = self.parse_clock_name(content)?;
+        let _ = <Token![/]>::parse(content)?;
+        let divider_lit = syn::LitInt::parse(content)?;
+        let divider: u64 = divider_lit.base10_parse()?;
+        if divider == 0 {
+            return Err(syn::Error::new_spanned(divider_lit,"clock divider must be more than 0"));
+        }
+        let mut phase = 0;
+        if content.peek(syn::Ident) {
+            let kw = syn::Ident::parse(content)?;
+            if kw != CLOCK_PHASE_KEYWORD {
+                let t = format!("expected `phase` or `;` after clock divider, found {}", kw);
+                return Err(syn::Error::new_spanned(kw,t));
+            }
+            let phase_lit = syn::LitInt::parse(content)?;
+            phase = phase_lit.base10_parse()?;
+            if phase >= divider {
+                let t = format!("phase of clock {} must be less than its divider {}", name, divider);
+                return Err(syn::Error::new_spanned(phase_lit,t));
+            }
+        }
+        let _ = <Token![;]>::parse(content)?;
+        // the clocks are stored relative to the main clock, same as pcb_rs::Clock::divide
+        let clock = parent_divider.checked_mul(divider).and_then(|d| {
+            Some((d, parent_divider.checked_mul(phase)?.checked_add(parent_phase)?))
+        });
+        match clock {
+            Some(clock) => {
+                self.clocks.insert(name.to_string(), clock);
+                Ok(())
+            }
+            None => {
+                let t = format!("divider of clock {} is too large", name);
+                Err(syn::Error::new_spanned(name,t))
+            }
+        }
+    }
+
+    // parses the name of a declared clock, or main, and gives its divider and phase
+    fn parse_clock_name(&self, content: ParseStream) -> Result<(u64, u64)> {
+        let clock = syn::Ident::parse(content)?;
+        if clock == MAIN_CLOCK {
+            return Ok((1, 0));
+        }
+        match self.clocks.get(&clock.to_string()) {
+            Some(c) => Ok(*c),
+            None => {
+                let t = format!("clock {} is not declared, clocks must be declared before they are used", clock);
+                Err(syn::Error::new_spanned(clock,t))
+            }
+        }
+    }
+
+    // parses the connections, exposed pins and for loops till the end of the input
+    fn parse_statements(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        while !content.is_empty() {
+            if content.peek(Token![for]) {
+                self.parse_for(content, vars)?;
+                continue;
+            }
+            if content.fork().parse::<syn::Ident>().is_ok_and(|kw| kw == PIN_EXPOSE_KEYWORD) {
+                self.parse_expose(content, vars)?;
+            } else if content.fork().parse::<syn::Ident>().is_ok_and(|kw| kw == CONTENTION_KEYWORD) {
+                self.parse_contention(content, vars)?;
+            } else if content.fork().parse::<syn::Ident>().is_ok_and(|kw| kw == PULL_KEYWORD || kw == KEEP_KEYWORD) {
+                self.parse_pull(content, vars)?;
+            } else {
+                self.parse_connection(content, vars)?;
             }
+        }
+        Ok(())
+    }
 
-            if !chip_map.contains_key(&chip1) {
-                let t = format!("use of undeclared chip {}", chip1);
-                return Err(syn::Error::new_spanned(&chip1,t));
+    // parses `for i in 0..8 { ... }`, where the body is parsed once for each value of i,
+    // so it can have connections, exposed pins and nested loops using i in the indices
+    fn parse_for(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        use syn::parse::Parser;
+        let _ = <Token![for]>::parse(content)?;
+        let var = syn::Ident::parse(content)?;
+        let _ = <Token![in]>::parse(content)?;
+        let range = parse_index_range(content, vars)?;
+        let body;
+        let _ = syn::braced!(body in content);
+        let body: proc_macro2::TokenStream = body.parse()?;
+        let mut vars = vars.clone();
+        for i in range {
+            vars.insert(var.to_string(), i);
+            (|input: ParseStream| self.parse_statements(input, &vars)).parse2(body.clone())?;
+        }
+        Ok(())
+    }
+
+    // parses a chip name, or `fa[i]` for a chip of chip array, giving the name of the chip
+    fn parse_chip_name(&self, content: ParseStream, vars: &LoopVars) -> Result<syn::Ident> {
+        let chip = syn::Ident::parse(content)?;
+        if !content.peek(syn::token::Bracket) {
+            return Ok(chip);
+        }
+        let index;
+        let _ = syn::bracketed!(index in content);
+        let i = parse_index(&index, vars)?;
+        match self.chip_arrays.get(&chip.to_string()) {
+            None => {
+                let t = format!("chip {} is not declared as a chip array", chip);
+                Err(syn::Error::new_spanned(chip,t))
             }
+            Some(len) if i < 0 || i >= *len as i64 => {
+                let t = format!("index {} is out of bounds for chip array {} of {} chips", i, chip, len);
+                Err(syn::Error::new_spanned(chip,t))
+            }
+            Some(_) => Ok(syn::Ident::new(&indexed_name(&chip, i)?, chip.span())),
+        }
+    }
 
-            if !chip_map.contains_key(&chip2) {
-                let t = format!("use of undeclared chip {}", chip2);
-                return Err(syn::Error::new_spanned(&chip2,t));
+    // parses `c1::p1 - c2::p2;`
+    fn parse_connection(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        let chip_ident = self.parse_chip_name(content, vars)?;
+        let chip1 = chip_ident.to_string();
+        let _ = <Token![::]>::parse(content)?;
+        let pin1 = syn::Member::parse(content)?;
+        let pins1 = parse_pin_range(content, &pin1, vars)?;
+        // pin connection token is -
+        let _ = <Token![-]>::parse(content);
+        let chip2 = self.parse_chip_name(content, vars)?.to_string();
+        let _ = <Token![::]>::parse(content)?;
+        let pin2 = syn::Member::parse(content)?;
+        let pins2 = parse_pin_range(content, &pin2, vars)?;
+        let _ = <Token![;]>::parse(content)?;
+        let (pin1_span, pin2_span) = (pin1.span(), pin2.span());
+
+        // a bus connection like `c1::addr[0..8] - c2::a[0..8];` connects the pins one-to-one,
+        // so both sides must have the same number of pins
+        if pins1.len() != pins2.len(){
+            let t = format!("bus width mismatch : chip `{}` pin `{}` has {} pins, but chip `{}` pin `{}` has {} pins",chip1,pin_name(&pin1),pins1.len(),chip2,pin_name(&pin2),pins2.len());
+            return Err(syn::Error::new_spanned(&pin1,t));
+        }
+
+        if !self.chip_map.contains_key(&chip1) {
+            let t = format!("use of undeclared chip {}", chip1);
+            return Err(syn::Error::new_spanned(&chip_ident,t));
+        }
+
+        if !self.chip_map.contains_key(&chip2) {
+            let t = format!("use of undeclared chip {}", chip2);
+            return Err(syn::Error::new_spanned(&pin2,t));
+        }
+
+        for (pin1,pin2) in pins1.into_iter().zip(pins2){
+            if (&chip1,&pin1) == (&chip2,&pin2){
+                let t = format!("attempted to connect a pin to itself : chip `{}` pin `{}` appears to have a self-connection, which is redundant",chip1,pin1);
+                return Err(syn::Error::new_spanned(&chip_ident,t));
             }
 
             // now we know for sure that both chips are declared and exists in the map
 
-            let t = chip_map.get_mut(&chip1).unwrap();
+            let t = self.chip_map.get_mut(&chip1).unwrap();
             t.push(pin1.clone());
-            let t = chip_map.get_mut(&chip2).unwrap();
+            let t = self.chip_map.get_mut(&chip2).unwrap();
             t.push(pin2.clone());
 
             let chip_pin1 = __ChipPin {
-                chip: chip1,
+                chip: chip1.clone(),
                 pin: pin1,
             };
             let chip_pin2 = __ChipPin {
-                chip: chip2,
+                chip: chip2.clone(),
                 pin: pin2,
             };
+            self.pin_spans.push((chip_pin1.clone(), pin1_span));
+            self.pin_spans.push((chip_pin2.clone(), pin2_span));
+            self.connection_spans.push((chip_pin1.clone(), chip_pin2.clone(), pin1_span));
 
-            if let Some(l) = pin_connection_list.get_mut(&chip_pin1) {
+            if let Some(l) = self.pin_connection_list.get_mut(&chip_pin1) {
                 // we first check if pin1 is already an entry, if so then add pin2 to its set
                 l.insert(chip_pin2);
-            } else if let Some(l) = pin_connection_list.get_mut(&chip_pin2) {
+            } else if let Some(l) = self.pin_connection_list.get_mut(&chip_pin2) {
                 // else we check if pin2 is already an entry
                 l.insert(chip_pin1);
             } else {
                 let mut _t = HashSet::new();
                 _t.insert(chip_pin2);
-                pin_connection_list.insert(chip_pin1, _t);
-            }
-
-            // we have to parse it here for the next iteration
-
-            match syn::Ident::parse(&content) {
-                Result::Ok(i) => kw = i,
-                Result::Err(_) => {
-                    return Ok(PcbMacroInput {
-                        name,
-                        pin_connection_list,
-                        chip_map,
-                        exposed_pins: Vec::new(),
-                    })
-                }
+                self.pin_connection_list.insert(chip_pin1, _t);
             }
         }
-        // now here the kw should be exposed
-        loop {
-            let chip = syn::Ident::parse(&content)?.to_string();
-            let _ = <Token![::]>::parse(&content)?;
-            let pin = syn::Ident::parse(&content)?.to_string();
-            if !chip_map.contains_key(&chip) {
+        Ok(())
+    }
+
+    // parses `expose c1::p1(,c2::p2)* as p3;`
+    fn parse_expose(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        let _ = syn::Ident::parse(content)?;
+        // each of the pins can be a bus, in which case there is one exposed pin
+        // per pin of the bus, so we keep the pins for each of them
+        let mut pins:Vec<Vec<__ChipPin>> = Vec::new();
+        let mut spans:Vec<Vec<proc_macro2::Span>> = Vec::new();
+        // if we have something like `expose c1::p1,c2::p1 as p3;`
+        loop{
+            let chip = self.parse_chip_name(content, vars)?;
+            let _ = <Token![::]>::parse(content)?;
+            let pin = syn::Member::parse(content)?;
+            let pin_names = parse_pin_range(content, &pin, vars)?;
+            if !self.chip_map.contains_key(&chip.to_string()) {
                 let t = format!("use of undeclared chip in expose pin : {}", chip);
                 return Err(syn::Error::new_spanned(&chip,t));
             }
-
-            let mut pins = vec![__ChipPin{
-                chip,pin
-            }];
-            // if we have something like `expose c1::p1,c2::p1 as p3;`
-            if content.peek(Token![,]){
-                // we do have it like `expose c1::p1,c2::p1 as p3;`
-                loop{
-                    let _ = <Token![,]>::parse(&content)?;
-                    let chip = syn::Ident::parse(&content)?.to_string();
-                        let _ = <Token![::]>::parse(&content)?;
-                        let pin = syn::Ident::parse(&content)?.to_string();
-                        if !chip_map.contains_key(&chip) {
-                            let t = format!("use of undeclared chip in expose pin : {}", chip);
-                            return Err(syn::Error::new_spanned(&chip,t));
-                        }
-                        pins.push(__ChipPin{chip,pin});
-                        // if we have a comma, there are more pins, else we can exit the loop
-                        if content.peek(Token![,]){
-                            continue;
-                        }else{
-                            break;
-                        }
-                }
+            if pins.is_empty(){
+                pins = pin_names.iter().map(|_|Vec::new()).collect();
+                spans = pin_names.iter().map(|_|Vec::new()).collect();
             }
-            // now there must be an `as` keyword
-            let _ = <Token![as]>::parse(&content);
-            let as_name = syn::Ident::parse(&content)?.to_string();
-            let _ = <Token![;]>::parse(&content);
-            
-            exposed_pins.push(__ExposedPins { pins, as_name });
-            match syn::Ident::parse(&content) {
-                Result::Ok(i) => {
-                    if i != PIN_EXPOSE_KEYWORD {
-                        let t =format!("expected 'expose' found {} instead", i.to_string());
-                        return Err(syn::Error::new_spanned(i,t));
-                    }
-                }
-                // this just means we have completed the parsing
-                Result::Err(_) => break,
+            if pin_names.len() != pins.len(){
+                let t = format!("bus width mismatch in expose : chip `{}` pin `{}` has {} pins, expected {}",chip,pin_name(&pin),pin_names.len(),pins.len());
+                return Err(syn::Error::new_spanned(&pin,t));
             }
-        }
-
-        let mut temp = HashMap::new();
-        for ep in &exposed_pins{
-            for p in &ep.pins{
-                if temp.contains_key(p){
-                    let previous = temp.get(p).unwrap();
-                    let t = format!("pin exposed multiple times : chip {} pin {} is exposed as {} and {}",
-                        p.chip,p.pin,previous, ep.as_name
-                    );
-                    return Err(syn::Error::new_spanned(&name,t));
-                }else{
-                    temp.insert(p,&ep.as_name);
-                }
-                    
-                
+            for ((shorted,shorted_spans),pin_name) in pins.iter_mut().zip(spans.iter_mut()).zip(pin_names){
+                let chip_pin = __ChipPin{chip:chip.to_string(),pin:pin_name};
+                self.pin_spans.push((chip_pin.clone(),pin.span()));
+                shorted.push(chip_pin);
+                shorted_spans.push(pin.span());
+            }
+            // if we have a comma, there are more pins, else we can exit the loop
+            if content.peek(Token![,]){
+                let _ = <Token![,]>::parse(content)?;
+            }else{
+                break;
             }
         }
+        // now there must be an `as` keyword
+        let _ = <Token![as]>::parse(content);
+        let as_name = syn::Ident::parse(content)?;
+        let as_names = parse_pin_range(content, &as_name, vars)?;
+        let _ = <Token![;]>::parse(content);
+        if as_names.len() != pins.len(){
+            let t = format!("bus width mismatch in expose : exposed pin `{}` has {} pins, but the exposed chip pins have {}",as_name,as_names.len(),pins.len());
+            return Err(syn::Error::new_spanned(&as_name,t));
+        }
 
-        Ok(PcbMacroInput {
-            name,
-            pin_connection_list,
-            chip_map,
-            exposed_pins,
-        })
+        for ((pins,spans),as_name) in pins.into_iter().zip(spans).zip(as_names){
+            self.exposed_pins.push(__ExposedPins { pins, spans, as_name });
+        }
+        Ok(())
     }
-}
 
-impl Into<proc_macro2::TokenStream> for PcbMacroInput {
-    fn into(self) -> proc_macro2::TokenStream {
-        self.generate()
+
+
+    // parses `contention unknown;` for all the tristated groups, or `contention c1::p1 = wired_and;`
+    // for the group of c1::p1, where a bus range sets the policy of the group of each of the pins
+    fn parse_contention(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        let kw = syn::Ident::parse(content)?;
+        if !content.peek2(Token![::]) && !content.peek2(syn::token::Bracket) {
+            let policy = parse_contention_policy(content)?;
+            let _ = <Token![;]>::parse(content)?;
+            if self.contention_policy.is_some() {
+                return Err(syn::Error::new_spanned(kw,"contention policy for the pcb given multiple times"));
+            }
+            self.contention_policy = Some(policy);
+            return Ok(());
+        }
+        let pins = self.parse_group_pins(content, vars, "contention policy")?;
+        let _ = <Token![=]>::parse(content)?;
+        let policy = parse_contention_policy(content)?;
+        let _ = <Token![;]>::parse(content)?;
+        for pin in pins{
+            self.contention_policies.push((pin,policy.clone()));
+        }
+        Ok(())
     }
-}
 
-impl PcbMacroInput {
+    // parses `pull c1::p1 = <value>;`, where the value can be any expression of a type implementing
+    // pcb_rs::PinData, and `keep c1::p1;` for a bus keeper
+    fn parse_pull(&mut self, content: ParseStream, vars: &LoopVars) -> Result<()> {
+        let kw = syn::Ident::parse(content)?;
+        let pins = self.parse_group_pins(content, vars, "pull")?;
+        let pull = if kw == KEEP_KEYWORD {
+            quote!{pcb_rs::Pull::Keeper}
+        } else {
+            let _ = <Token![=]>::parse(content)?;
+            // the expression is taken as it is till the ;, and its type is checked against the pins when building
+            let mut value = proc_macro2::TokenStream::new();
+            while !content.is_empty() && !content.peek(Token![;]) {
+                value.extend([content.parse::<proc_macro2::TokenTree>()?]);
+            }
+            if value.is_empty() {
+                return Err(syn::Error::new_spanned(kw,"expected a value for the pull"));
+            }
+            quote!{pcb_rs::Pull::value(#value)}
+        };
+        let _ = <Token![;]>::parse(content)?;
+        for pin in pins{
+            self.pulls.push((pin,pull.clone()));
+        }
+        Ok(())
+    }
 
+    // parses `c1::p1`, or a bus range of pins, for which a contention policy or pull is given.
+    // The pins are also added to the pins of the chip, so they get checked to be present when building
+    fn parse_group_pins(&mut self, content: ParseStream, vars: &LoopVars, statement: &str) -> Result<Vec<__ChipPin>> {
+        let chip = self.parse_chip_name(content, vars)?;
+        let _ = <Token![::]>::parse(content)?;
+        let pin = syn::Member::parse(content)?;
+        let pins = parse_pin_range(content, &pin, vars)?;
+        let chip_pins = match self.chip_map.get_mut(&chip.to_string()) {
+            Some(chip_pins) => chip_pins,
+            None => {
+                let t = format!("use of undeclared chip in {} : {}", statement, chip);
+                return Err(syn::Error::new_spanned(&chip,t));
+            }
+        };
+        chip_pins.extend(pins.iter().cloned());
+        let pins:Vec<_> = pins.into_iter().map(|pin|__ChipPin{chip:chip.to_string(),pin}).collect();
+        self.pin_spans.extend(pins.iter().map(|p|(p.clone(),pin.span())));
+        Ok(pins)
+    }
 
     // This might be more efficiently implemented, I think this has worst case O(n^2)?
     fn get_short_pin_set(&self)->Vec<Vec<__ChipPin>>{
@@ -238,16 +691,12 @@ impl PcbMacroInput {
         // this is the final return, which is the collection of groups of all the pins that are 
         // shorted, i.e. connected electrically, so that voltage at any
         // one fo the pins in the individual group will affect rest of the pins in that group
-        let mut shorted_pins = Vec::new();
+        let mut shorted_pins:Vec<Vec<__ChipPin>> = Vec::new();
 
-        loop{
+        // we take a set from the initial sets, if no sets are remaining,
+        // work is done
+        while let Some(mut set) = initial_collection.pop(){
 
-            // we take a set from the initial sets, if no sets are remaining,
-            // work is done
-            let mut set = match initial_collection.pop(){
-                Some(s)=>s,
-                None=>break
-            };
             // a temp vector to store the groups which does not have any pins in common
             // with the set above
             let mut t = Vec::new();
@@ -258,7 +707,7 @@ impl PcbMacroInput {
             // the temp vector
             for s in initial_collection{
                 if set.intersection(&s).next().is_some(){
-                    set.extend(s.into_iter());
+                    set.extend(s);
                 }else{
                     t.push(s);
                 }
@@ -271,6 +720,13 @@ impl PcbMacroInput {
             initial_collection = t;
         }
 
+        // the sets and hashmap give the pins in a different order on each compilation,
+        // so we sort them to generate the same code each time
+        for group in &mut shorted_pins{
+            group.sort();
+        }
+        shorted_pins.sort();
+
         // return shorted pins
         shorted_pins
     }
@@ -279,93 +735,57 @@ impl PcbMacroInput {
         let pcb_name = &self.name;
         let builder_name = quote::format_ident!("{}Builder", pcb_name);
 
-        let chip_names = self.chip_map.iter().map(|(name, _)| quote! {#name});
+        let chip_names = self.chip_priorities.iter().map(|(name,_)| quote! {#name});
 
-        let chip_pin_check = self.chip_map.iter().map(|(name,pins)|{
-            if pins.is_empty(){
-                return quote!{};
-            }
-            let pin_names = pins.iter().map(|n|{quote!{#n}});
-            let exposed_pins:Vec<_> = self.exposed_pins.iter()
-                .flat_map(|ep|&ep.pins)
-                .filter(|p|{p.chip == *name})
-                .map(|cp|&cp.pin)
-                .map(|n|quote!{#n})
-                .collect();
-            let exposed_pin_check = if exposed_pins.len() == 0{
-                quote!{}
-            }else{
-                let names = exposed_pins.iter();
-                quote!{
-                    for pin in [#(#names),*]{
-                        if !chip_pins.contains_key(pin){
-                            return std::result::Result::Err(format!("Invalid chip added : chip {} expected to have pin named {}, not found",#name,pin));
-                        }
-                    }
-                }
-            };
-            quote!{
-                let chip = self.added_chip_map.get(#name).unwrap();
-                let chip_pins = chip.get_pin_list();
-                for pin in [#(#pin_names),*]{
-                    if !chip_pins.contains_key(pin){
-                        return std::result::Result::Err(format!("Invalid chip added : chip {} expected to have pin named {}, not found",#name,pin));
-                    }
-                }
-                #exposed_pin_check
-            }
-        });
+        // chips are ticked in the order of their priority, with higher priority chips first,
+        // and in the order of declaration for the chips with same priority
+        let tick_order = {
+            let mut order:Vec<_> = self.chip_priorities.iter().collect();
+            // this is a stable sort, so declaration order is kept for same priorities
+            order.sort_by_key(|(_,priority)|std::cmp::Reverse(*priority));
+            order.into_iter().map(|(name,_)|name)
+        };
 
-        // TODO improve this!
-        // this will bind some variables to the actual entered chips for the builder
-        let instantiate_chip_vars = self.chip_map.iter().map(|(name, _)| {
-            if self.pin_connection_list.is_empty(){
-                quote!{}
-            }else{
-                
-                let __name = quote::format_ident!("_{}",&name);
-                quote! {let #__name = self.added_chip_map.get(#name).unwrap().get_pin_list();}
-            }
+        // clocks of the chips, in the tick order
+        let chip_clocks = tick_order.clone().map(|name|{
+            let (divider,phase) = self.chip_clocks.get(name).copied().unwrap_or((1,0));
+            quote!{pcb_rs::Clock{divider:#divider,phase:#phase}}
         });
 
-        
+        // the clocks of the chips in clock domains, which are checked by pcb_rs::check_valid_chips
+        let clocked_chips = self.chip_priorities.iter()
+            .filter_map(|(name,_)| self.chip_clocks.get(name).map(|clock|(name,clock)))
+            .filter(|(_,clock)| **clock != (1,0))
+            .map(|(name,(divider,phase))| quote!{
+                (#name,pcb_rs::Clock{divider:#divider,phase:#phase})
+            });
 
-        let pin_connection_checks = self
-            .pin_connection_list
-            .iter()
-            .map(|(pin, connected_pins)| {
-                let _chip = &pin.chip;
-                let _pin = &pin.pin;
-                let chip_ident = quote::format_ident!("_{}",_chip);
-                let connected_pin_iter = connected_pins.iter().map(|pin| {
-                    let __chip = &pin.chip;
-                    let __pin = &pin.pin;
-                    let chip_ident = quote::format_ident!("_{}",__chip);
-                    quote! {
-                        let __pin2 = #chip_ident.get(#__pin).unwrap();
-                        if !__pin1.is_connectable(__pin2){
-                            return std::result::Result::Err(
-                                format!("Invalid chip connection : cannot connect chip {}'s pin {} ({:?}) to chip {}'s pin {} ({:?})",
-                                    #_chip,#_pin,__pin1,#__chip,#__pin,__pin2
-                                )
-                            )
-                        }
-                        self.pin_metadata_cache.insert(pcb_rs::ChipPin{
-                            chip: #__chip,
-                            pin: #__pin,
-                        },*__pin2);
-                    }
-                });
+        // the connections in order of declaration, which the builder validates using the same functions
+        // as pcb_rs::DynamicPcbBuilder. A connection given multiple times is only checked once
+        let mut connection_list:Vec<(&__ChipPin,&__ChipPin)> = Vec::with_capacity(self.connection_spans.len());
+        for (pin1,pin2,_) in &self.connection_spans{
+            if !connection_list.iter().any(|c| *c == (pin1,pin2) || *c == (pin2,pin1)){
+                connection_list.push((pin1,pin2));
+            }
+        }
+        let connections = connection_list.iter().map(|(pin1,pin2)|{
+            let (chip1,pin1) = (&pin1.chip,&pin1.pin);
+            let (chip2,pin2) = (&pin2.chip,&pin2.pin);
+            quote!{
+                (pcb_rs::ChipPin{chip:#chip1,pin:#pin1},pcb_rs::ChipPin{chip:#chip2,pin:#pin2})
+            }
+        });
 
-                quote! {
-                    let __pin1 = #chip_ident.get(#_pin).unwrap();
-                    self.pin_metadata_cache.insert(pcb_rs::ChipPin{
-                        chip:#_chip,
-                        pin:#_pin
-                    },*__pin1);
-                    #(#connected_pin_iter)*   
-                }
+        let exposed_pin_list = self.exposed_pins.iter().map(|ep|{
+            let as_name = &ep.as_name;
+            let pins = ep.pins.iter().map(|cp|{
+                let (chip,pin) = (&cp.chip,&cp.pin);
+                quote!{pcb_rs::ChipPin{chip:#chip,pin:#pin}}
             });
+            quote!{
+                (std::vec![#(#pins),*],#as_name)
+            }
+        });
 
         let shorted_pins = self.get_short_pin_set();
 
@@ -394,290 +814,399 @@ impl PcbMacroInput {
             }
         }
 
-        let shorted_pins_tokens = shorted_pins.iter().map(|group|{
-            let g = group.iter().map(|cp|{
-                let chip = &cp.chip;
-                let pin= &cp.pin;
-                quote!{
-                    pcb_rs::ChipPin{
-                        chip:#chip,
-                        pin:#pin
-                    }
-                }
-            });
-            quote!{
-                std::vec![#(#g),*]
-            }
-        });
-
-        // TODO add a test to verify this
-        let exposed_pin_type_check = {
-            let instantiate_chip_vars = self.chip_map.iter().map(|(name, _)| {    
-                let __name = quote::format_ident!("_{}",&name);
-                quote! {let #__name = self.added_chip_map.get(#name).unwrap().get_pin_list();}
-            });
-
-            let checks = self.exposed_pins.iter().map(|ep|{
-                if ep.pins.len() == 1{
-                    // skip for single (non-shorted) exposed pin
-                    return quote!{};
-                }
-                let zeroth_chip = &ep.pins[0].chip;
-                let zeroth_pin = &ep.pins[0].pin;
-                let zeroth_chip_ident = quote::format_ident!("_{}",zeroth_chip);
-                let zeroth_extracted = quote!{
-                    let first_type = &#zeroth_chip_ident.get(#zeroth_pin).unwrap().data_type;
-                };
-                let pin_checks = ep.pins.iter().map(|p|{
-                    let _chip = &p.chip;
-                    let _pin = &p.pin;
-                    let chip_ident = quote::format_ident!("_{}",_chip);
-                    quote!{
-                        let md = #chip_ident.get(#_pin).unwrap();
-                        if !matches!(md.pin_type,pcb_rs::PinType::Input){
-                            return std::result::Result::Err(format!(
-                                "chip {} pin {} is expected to be input type, as it is exposed and shorted with other pins, but was not. only input type pins are allowed to be shorted when exposing",
-                                #_chip,#_pin
-                            ));
-                        }
-                        if md.data_type != *first_type{
-                            return std::result::Result::Err(format!(
-                                "chip {} pin {} is expected to be of {} type, as it is shorted with pin of that type, but was found to be of {} type",
-                                #_chip,#_pin,first_type,md.data_type
-                            ));
-                        }
-                    }
-                });
-                quote!{
-                    #zeroth_extracted
-                    #(#pin_checks)*
-                }
-            });
-
-
-            quote!{
-                #(#instantiate_chip_vars)*
-                #(#checks)*
-            }
-        };
-
         // ci is ChipInterface
         
-        let ci_pin_map = self.exposed_pins.iter().map(|ep|{
+        let ci_pin_map = self.exposed_pins.iter().enumerate().map(|(i,ep)|{
             // note that metadata for all shorted exposed pins must be same, which
             // will be verified at building time, so we can just give metadata of first pin
             // and in case there is a single pin, it will be 0th
-            let pin_name = &ep.pins[0].pin;
-            let chip_name = &ep.pins[0].chip;
             let as_name = &ep.as_name;
             quote!{
-                let __chip = self.chips.get(#chip_name).unwrap();
-                let md = __chip.get_pin_list().get(#pin_name).unwrap().clone();
+                let __pin = &self.exposed_pins[#i][0];
+                let md = self.chips[__pin.chip].get_pin_list().get(__pin.name.pin).unwrap().clone();
                 ret.insert(#as_name,md);
             }
         });
 
-        let ci_get_value = self.exposed_pins.iter().map(|ep|{
-            // note that the shorted pins must be of input type, shorting multiple o/p
-            // is not valid and will error at building time
-            // thus, this is only valid when all pins are input type, and as all are shorted,
-            // we can just give 0th pin's value
-            let pin_name = &ep.pins[0].pin;
-            let chip_name = &ep.pins[0].chip;
+        // the index of an exposed pin is its position in the expose statements
+        let ci_pin_index = self.exposed_pins.iter().enumerate().map(|(i,ep)|{
             let as_name = &ep.as_name;
             quote!{
-                #as_name =>{
-                    let __chip = self.chips.get(#chip_name).unwrap();
-                    return __chip.get_pin_value(#pin_name);
-                }
+                #as_name => std::option::Option::Some(#i)
             }
         });
 
-        let ci_set_value = self.exposed_pins.iter().map(|ep|{
-            let t = ep.pins.iter().map(|cp|{
+        // the exposed pins are resolved to the chip and pin indices when building,
+        // in the same order as the expose statements
+        let resolve_exposed_pins = self.exposed_pins.iter().map(|ep|{
+            let pins = ep.pins.iter().map(|cp|{
                 let pin_name = &cp.pin;
                 let chip_name = &cp.chip;
                 quote!{
-                    let __chip = self.chips.get_mut(#chip_name).unwrap();
-                    if ! __chip.is_pin_tristated(#chip_name){
-                        __chip.set_pin_value(#pin_name,val);
-                    }
+                    pcb_rs::resolve_pin(&chip_names,&chips,pcb_rs::ChipPin{chip:#chip_name,pin:#pin_name})
                 }
             });
-            let as_name = &ep.as_name;
             quote!{
-                #as_name =>{
-                    #(#t)*
-                    return;
-                }
+                std::vec![#(#pins),*]
             }
         });
 
-        let ci_pin_tristated = self.exposed_pins.iter().map(|ep|{
-            // as the only pins to be grouped in expose are of input type,
-            // so it does not make sense to ask if the pins are tristated or not,
-            // as that majorly matters when the pin is output type.
-            // and when setting pins values, pins which are tristated are ignored anyways,
-            // so we can just return false in that case
-            let as_name = &ep.as_name;
-            if ep.pins.len() == 1{
-                let chip_name = &ep.pins[0].chip;
-                let pin_name = &ep.pins[0].pin;
-                quote!{
-                    #as_name =>{
-                        let __chip = self.chips.get(#chip_name).unwrap();
-                        return __chip.is_pin_tristated(#pin_name);
-                    }
-                }
+        let contention_policy = self.contention_policy.clone().unwrap_or_else(||quote!{pcb_rs::ContentionPolicy::Panic});
+        let contention_policies = self.contention_policies.iter().map(|(cp,policy)|{
+            let chip = &cp.chip;
+            let pin = &cp.pin;
+            quote!{
+                (pcb_rs::ChipPin{chip:#chip,pin:#pin},#policy)
+            }
+        });
+
+        let pulls = self.pulls.iter().map(|(cp,pull)|{
+            let chip = &cp.chip;
+            let pin = &cp.pin;
+            quote!{
+                (pcb_rs::ChipPin{chip:#chip,pin:#pin},#pull)
+            }
+        });
+
+        // the builder has a type parameter for each typed chip, which is ChipMissing till the chip is added
+        // using its method, and build is only implemented when all of them are ChipAdded, so
+        // missing typed chips are compile errors. Pcbs without typed chips have no type parameters
+        let chip_states:Vec<_> = (0..self.chip_types.len()).map(|i|quote::format_ident!("__C{}",i)).collect();
+        let builder_generics = if chip_states.is_empty(){
+            quote!{}
+        }else{
+            quote!{<#(#chip_states),*>}
+        };
+        let builder_with_states = |state:proc_macro2::TokenStream|{
+            if chip_states.is_empty(){
+                quote!{#builder_name}
             }else{
-                quote!{
-                    #as_name =>{
-                        false
+                let states = chip_states.iter().map(|_|&state);
+                quote!{#builder_name<#(#states),*>}
+            }
+        };
+        let new_builder = builder_with_states(quote!{pcb_rs::ChipMissing});
+        let complete_builder = builder_with_states(quote!{pcb_rs::ChipAdded});
+
+        let typed_chip_adders = self.chip_types.iter().enumerate().map(|(i,(name,ty))|{
+            let method = quote::format_ident!("{}",name);
+            let states = chip_states.iter().enumerate().map(|(j,state)|{
+                if i == j { quote!{pcb_rs::ChipAdded} } else { quote!{#state} }
+            });
+            let doc = format!("adds the chip declared as {} in the pcb",name);
+            quote!{
+                #[doc = #doc]
+                pub fn #method(mut self,chip:#ty)->#builder_name<#(#states),*>{
+                    self.added_chip_map.insert(std::string::String::from(#name),std::boxed::Box::new(chip));
+                    #builder_name{
+                        added_chip_map:self.added_chip_map,
+                        contention_policy:self.contention_policy,
+                        contention_policies:self.contention_policies,
+                        pulls:self.pulls,
+                        chip_states:std::marker::PhantomData
                     }
                 }
             }
-            
         });
 
-        let ci_pin_input_mode = self.exposed_pins.iter().map(|ep|{
-            // as only input type pins are allowed to be shorted , in case there are shorted pins, 
-            // they will have to be in input type
-            let as_name = &ep.as_name;
-            if ep.pins.len() == 1{
-                let chip_name = &ep.pins[0].chip;
-                let pin_name = &ep.pins[0].pin;
-                quote!{
-                    #as_name =>{
-                        let __chip = self.chips.get(#chip_name).unwrap();
-                        return __chip.in_input_mode(#pin_name);
+        // typed chips can still be replaced using add_chip, so their types are checked when building
+        let chip_type_checks = self.chip_types.iter().map(|(name,ty)|{
+            quote!{
+                if let std::option::Option::Some(chip) = self.added_chip_map.get(#name){
+                    let chip:&dyn pcb_rs::HardwareModule = &**chip;
+                    if !chip.is::<#ty>(){
+                        errors.push(pcb_rs::BuildError::ChipTypeMismatch{
+                            chip:#name,
+                            expected:std::any::type_name::<#ty>()
+                        });
                     }
                 }
-            }else{
-                quote!{
-                    #as_name =>{
-                        return true;
-                    }
+            }
+        });
+
+        let typed_chip_getters = self.chip_types.iter().map(|(name,ty)|{
+            let method = quote::format_ident!("{}",name);
+            let method_mut = quote::format_ident!("{}_mut",name);
+            let doc = format!("gives the chip declared as {} in the pcb",name);
+            quote!{
+                #[doc = #doc]
+                pub fn #method(&self)->&#ty{
+                    // the type of the chip is checked when building, so this cannot fail
+                    self.get_chip(#name).unwrap()
+                }
+
+                #[doc = #doc]
+                pub fn #method_mut(&mut self)->&mut #ty{
+                    self.get_chip_mut(#name).unwrap()
                 }
             }
-            
         });
 
+        // the pins of typed chips are known when compiling, so the checks for those which do not
+        // need the data types are done in const items, and give compile errors at the statements
+        // only the types implementing ChipPins give their pins, see pcb_rs::PinsOf, the others are
+        // checked when building. The NoChipPins trait must be in scope where these are used
+        let chip_type_of = |chip:&String|self.chip_types.iter().find(|(name,_)|name == chip).map(|(_,ty)|ty);
+        let pins_of = |ty:&syn::Type| quote!{pcb_rs::PinsOf::<#ty>::PINS};
+        let pin_assertions = {
+            let mut checked:HashSet<&__ChipPin> = HashSet::new();
+            let mut assertions:Vec<proc_macro2::TokenStream> = Vec::new();
+            for (cp,span) in &self.pin_spans{
+                let ty = match chip_type_of(&cp.chip){
+                    Some(ty) => ty,
+                    None => continue
+                };
+                // only the first use of a pin is checked, so there is a single error for it
+                if !checked.insert(cp){
+                    continue;
+                }
+                let pin = &cp.pin;
+                let msg = format!("chip {} does not have a pin named {}",cp.chip,cp.pin);
+                let pins = pins_of(ty);
+                assertions.push(quote_spanned!{*span=>
+                    if let std::option::Option::Some(pins) = #pins{
+                        if pcb_rs::find_pin(pins,#pin).is_none(){
+                            panic!(#msg);
+                        }
+                    }
+                });
+            }
+            for (cp1,cp2,span) in &self.connection_spans{
+                let (ty1,ty2) = match (chip_type_of(&cp1.chip),chip_type_of(&cp2.chip)){
+                    (Some(ty1),Some(ty2)) => (ty1,ty2),
+                    _ => continue
+                };
+                let (pin1,pin2) = (&cp1.pin,&cp2.pin);
+                let msg = format!("pin {}::{} cannot be connected to pin {}::{}, as both are inputs, both are outputs, or only one of them is tristatable",cp1.chip,cp1.pin,cp2.chip,cp2.pin);
+                let (pins1,pins2) = (pins_of(ty1),pins_of(ty2));
+                assertions.push(quote_spanned!{*span=>
+                    if let (std::option::Option::Some(pins1),std::option::Option::Some(pins2)) = (#pins1,#pins2){
+                        if let (std::option::Option::Some(p1),std::option::Option::Some(p2)) = (
+                            pcb_rs::find_pin(pins1,#pin1),
+                            pcb_rs::find_pin(pins2,#pin2)
+                        ){
+                            if !p1.is_connectable(&p2){
+                                panic!(#msg);
+                            }
+                        }
+                    }
+                });
+            }
+            for ep in self.exposed_pins.iter().filter(|ep|ep.pins.len() > 1){
+                for (cp,span) in ep.pins.iter().zip(&ep.spans){
+                    let ty = match chip_type_of(&cp.chip){
+                        Some(ty) => ty,
+                        None => continue
+                    };
+                    let pin = &cp.pin;
+                    let msg = format!("pin {}::{} is shorted in exposed pin {}, but is not an input pin. Only input pins can be shorted when exposing",cp.chip,cp.pin,ep.as_name);
+                    let pins = pins_of(ty);
+                    assertions.push(quote_spanned!{*span=>
+                        if let std::option::Option::Some(pins) = #pins{
+                            if let std::option::Option::Some(p) = pcb_rs::find_pin(pins,#pin){
+                                if !matches!(p.pin_type,pcb_rs::PinType::Input){
+                                    panic!(#msg);
+                                }
+                            }
+                        }
+                    });
+                }
+            }
+            // each check is its own const item, as evaluation of a const stops at the first panic,
+            // and we want the errors of all the statements
+            quote!{
+                #(const _:() = { use pcb_rs::NoChipPins as _; #assertions };)*
+            }
+        };
+
+        // the pcb gets the pins of the chips it exposes, so its pins are known when the chips of all
+        // exposed pins are typed, and give their pins. The first pin of shorted exposed pins is used,
+        // same as for the pin list
+        let pcb_chip_pins = if self.exposed_pins.iter().all(|ep|chip_type_of(&ep.pins[0].chip).is_some()){
+            let exposed_chip_pins:Vec<_> = self.exposed_pins.iter().map(|ep|pins_of(chip_type_of(&ep.pins[0].chip).unwrap())).collect();
+            let pins = self.exposed_pins.iter().zip(&exposed_chip_pins).map(|(ep,chip_pins)|{
+                let pin = &ep.pins[0].pin;
+                let as_name = &ep.as_name;
+                quote!{pcb_rs::exposed_pin(#chip_pins,#pin,#as_name)}
+            });
+            quote!{
+                impl pcb_rs::ChipPins for #pcb_name{
+                    const PINS: &'static [pcb_rs::PinDescriptor] = {
+                        use pcb_rs::NoChipPins as _;
+                        &[#(#pins),*]
+                    };
+                    const KNOWN: bool = {
+                        use pcb_rs::NoChipPins as _;
+                        true #(&& #exposed_chip_pins.is_some())*
+                    };
+                }
+            }
+        }else{
+            quote!{}
+        };
+
         quote! {
-            
-            pub struct #builder_name{
-                added_chip_map:std::collections::HashMap<std::string::String,std::boxed::Box<dyn pcb_rs::HardwareModule>>,
-                shorted_pins:std::vec::Vec<std::vec::Vec<pcb_rs::ChipPin>>,
-                pin_metadata_cache:std::collections::HashMap<pcb_rs::ChipPin,pcb_rs::PinMetadata>
+            #pin_assertions
+
+            #pcb_chip_pins
+
+            pub struct #builder_name #builder_generics{
+                added_chip_map:std::collections::HashMap<std::string::String,std::boxed::Box<pcb_rs::PcbChip>>,
+                contention_policy:pcb_rs::ContentionPolicy,
+                contention_policies:std::vec::Vec<(pcb_rs::ChipPin,pcb_rs::ContentionPolicy)>,
+                pulls:std::vec::Vec<(pcb_rs::ChipPin,pcb_rs::Pull)>,
+                chip_states:std::marker::PhantomData<(#(#chip_states,)*)>
             }
 
-            impl #builder_name{
+            impl #new_builder{
 
                 pub fn new()->Self{
-                    let shorted = std::vec![#(#shorted_pins_tokens),*];
                     Self{
                         added_chip_map:std::collections::HashMap::new(),
-                        shorted_pins:shorted,
-                        pin_metadata_cache:std::collections::HashMap::new()
+                        contention_policy:#contention_policy,
+                        contention_policies:std::vec![#(#contention_policies),*],
+                        pulls:std::vec![#(#pulls),*],
+                        chip_states:std::marker::PhantomData
                     }
                 }
+            }
+
+            impl #builder_generics #builder_name #builder_generics{
 
-                pub fn add_chip(mut self,name:&str,chip: std::boxed::Box<dyn pcb_rs::HardwareModule>)->Self{
+                pub fn add_chip(mut self,name:&str,chip: std::boxed::Box<pcb_rs::PcbChip>)->Self{
                     self.added_chip_map.insert(name.to_string(),chip);
                     self
                 }
 
-                pub fn build(mut self)->std::result::Result<#pcb_name, std::string::String>{
-                    self.check_added_all_chips()?;
-                    self.check_valid_chips()?;
-                    // this will validate pin connections as well as set up
-                    // the pin metadata in hashmap
-                    self.check_valid_pin_connection()?;
-                    self.check_exposed_pin_types()?;
-                    let pin_connections = self.get_pin_connections()?;
-
-                    std::result::Result::Ok(#pcb_name{
-                        chips:self.added_chip_map,
-                        pin_connections
-                    })
-                }
-
-                fn check_added_all_chips(&self)-> std::result::Result<(),std::string::String>{
-                    for chip in [#(#chip_names),*]{
-                        if !self.added_chip_map.contains_key(chip){
-                            return std::result::Result::Err(format!("chip {} defined in pcb design, but not added",chip))
-                        }
-                    }
-                    std::result::Result::Ok(())
-                }
-                fn check_valid_chips(&self)-> std::result::Result<(),std::string::String>{
-                    #(#chip_pin_check)*
-                    std::result::Result::Ok(())
+                /// sets the contention policy for all the tristated groups, overriding the one given in the pcb!
+                pub fn contention_policy(mut self,policy:pcb_rs::ContentionPolicy)->Self{
+                    self.contention_policy = policy;
+                    self
                 }
 
-                // yes this does two things by also setting the chip metadata in hashmap, but otherwise there
-                // would have been a lot of code duplication, so go with it for now
-                fn check_valid_pin_connection(&mut self)->std::result::Result<(),std::string::String>{
-                    #(#instantiate_chip_vars)*
-                    #(#pin_connection_checks)*
-                    
-                    std::result::Result::Ok(())
+                /// sets the contention policy for the tristated group of the given pin
+                pub fn bus_contention_policy(mut self,pin:pcb_rs::ChipPin,policy:pcb_rs::ContentionPolicy)->Self{
+                    self.contention_policies.push((pin,policy));
+                    self
                 }
 
-                fn check_exposed_pin_types(&self)->std::result::Result<(),std::string::String>{
-                    #exposed_pin_type_check
-
-                    std::result::Result::Ok(())
+                /// sets the pull of the tristated group of the given pin, overriding the one given in the pcb!
+                pub fn pull(mut self,pin:pcb_rs::ChipPin,pull:pcb_rs::Pull)->Self{
+                    self.pulls.push((pin,pull));
+                    self
                 }
 
-                // This function can be optimized a bit by removing multiple iter() and map() calls
-                // some of might be redundant
-                fn get_pin_connections(&self)->std::result::Result<std::vec::Vec<pcb_rs::ConnectedPins>,std::string::String>{
-                    use std::vec::Vec;
-                    use pcb_rs::{ChipPin,PinType,ConnectedPins,PinMetadata};
-
-                    let mut ret:Vec<ConnectedPins> = Vec::with_capacity(self.shorted_pins.len());
-                    for group in &self.shorted_pins{
-                        let input_pins = group.iter().filter(|pin|{
-                            let md = self.pin_metadata_cache.get(pin).unwrap();
-                            matches!(md.pin_type,pcb_rs::PinType::Input) || matches!(md.pin_type,pcb_rs::PinType::IO)
-                        }).map(|pin|(*pin,self.pin_metadata_cache.get(pin).unwrap())).collect();
+                #(#typed_chip_adders)*
+            }
 
-                        let output_pins = group.iter().filter(|pin|{
-                            let md = self.pin_metadata_cache.get(pin).unwrap();
-                            matches!(md.pin_type,pcb_rs::PinType::Output) || matches!(md.pin_type,pcb_rs::PinType::IO)
-                        }).map(|pin|(*pin,self.pin_metadata_cache.get(pin).unwrap())).collect();
-                        
-                        ret.push(pcb_rs::get_pin_group(input_pins,output_pins)?);
+            impl #complete_builder{
+
+                pub fn build(self)->std::result::Result<#pcb_name, std::vec::Vec<pcb_rs::BuildError>>{
+                    let mut errors = std::vec::Vec::new();
+                    let connections:&[(pcb_rs::ChipPin,pcb_rs::ChipPin)] = &[#(#connections),*];
+                    let exposed_pins:std::vec::Vec<(std::vec::Vec<pcb_rs::ChipPin>,&'static str)> = std::vec![#(#exposed_pin_list),*];
+                    let used_pins = pcb_rs::used_pins(connections,&exposed_pins,&self.contention_policies,&self.pulls);
+                    pcb_rs::check_added_chips(&self.added_chip_map,&[#(#chip_names),*],&used_pins,&mut errors);
+                    self.check_chip_types(&mut errors);
+                    pcb_rs::check_valid_chips(&self.added_chip_map,&used_pins,&[#(#clocked_chips),*],&mut errors);
+                    // rest of the checks need all the chips and their pins to be present,
+                    // so we cannot go further if they are not
+                    if !errors.is_empty(){
+                        return std::result::Result::Err(errors);
+                    }
+                    let pin_metadata = pcb_rs::get_pin_metadata(&self.added_chip_map,&used_pins);
+                    pcb_rs::check_pin_connections(connections,&pin_metadata,&mut errors);
+                    let shorted_pins = pcb_rs::get_short_pin_set(connections);
+                    pcb_rs::check_exposed_pins(&exposed_pins,&shorted_pins,&pin_metadata,&mut errors);
+                    let pin_connections = pcb_rs::get_connected_pins(&shorted_pins,&pin_metadata,&mut errors);
+                    if !errors.is_empty(){
+                        return std::result::Result::Err(errors);
+                    }
+                    let tick_order = std::vec![#(std::string::String::from(#tick_order)),*];
+                    // the chips are stored in the tick order, and all the pins are resolved to
+                    // their chip and pin indices here, so no names need to be looked up when ticking
+                    let (chip_names,chips) = pcb_rs::into_chip_slots(self.added_chip_map,&tick_order,&mut errors);
+                    let mut resolved_connections = pcb_rs::resolve_connections(&chip_names,&chips,&pin_connections);
+                    pcb_rs::set_contention_policies(&mut resolved_connections,&chips,self.contention_policy,&self.contention_policies,&mut errors);
+                    pcb_rs::set_pulls(&mut resolved_connections,&chips,self.pulls,&mut errors);
+                    if !errors.is_empty(){
+                        return std::result::Result::Err(errors);
                     }
+                    let combinational_chips = pcb_rs::get_combinational_chips(&chips);
+                    let falling_edge = chips.iter().any(|chip|chip.uses_falling_edge());
+                    let exposed_pins = std::vec![#(#resolve_exposed_pins),*];
 
-                    Ok(ret)
+                    std::result::Result::Ok(#pcb_name{
+                        chip_names,
+                        chips,
+                        pin_connections,
+                        resolved_connections,
+                        combinational_chips,
+                        exposed_pins,
+                        chip_clocks:std::vec![#(#chip_clocks),*],
+                        cycle:0,
+                        falling_edge,
+                        contentions:std::vec::Vec::new(),
+                        scratch:std::default::Default::default()
+                    })
                 }
 
+                // this is empty when there are no typed chips, which clippy does not like
+                #[allow(clippy::ptr_arg)]
+                fn check_chip_types(&self,errors:&mut std::vec::Vec<pcb_rs::BuildError>){
+                    #(#chip_type_checks)*
+                }
             }
 
             pub struct #pcb_name{
-                chips:std::collections::HashMap<std::string::String,std::boxed::Box<dyn pcb_rs::HardwareModule>>,
-                pin_connections:std::vec::Vec<pcb_rs::ConnectedPins>
+                // names of the chips, in the same order as chips
+                chip_names:std::vec::Vec<std::string::String>,
+                // chips are stored in the tick order
+                chips:std::vec::Vec<std::boxed::Box<pcb_rs::PcbChip>>,
+                pin_connections:std::vec::Vec<pcb_rs::ConnectedPins>,
+                resolved_connections:std::vec::Vec<pcb_rs::ResolvedPins>,
+                combinational_chips:std::vec::Vec<(usize,std::vec::Vec<pcb_rs::PinIndex>)>,
+                exposed_pins:std::vec::Vec<std::vec::Vec<pcb_rs::PinSlot>>,
+                // clocks of the chips, in the same order as chips, and the number of ticks since building or reset
+                chip_clocks:std::vec::Vec<pcb_rs::Clock>,
+                cycle:u64,
+                // if any of the chips use the falling edge, in which case the pcb drives both the edges
+                falling_edge:bool,
+                // contentions recorded with Error and Unknown policies in the last tick, till they are taken
+                contentions:std::vec::Vec<pcb_rs::Contention>,
+                // buffers reused by the propagation on each tick
+                scratch:pcb_rs::ScratchBuffers
             }
 
             impl #pcb_name{
+                // the chips are upcast to HardwareModule for downcasting, as they are SendHardwareModule with the `parallel` feature
                 pub fn get_chip<'s,T:pcb_rs::HardwareModule>(&'s self,chip:&str)->std::option::Option<&'s T>{
-                    match self.chips.get(chip){
+                    match self.chip_names.iter().position(|n|n == chip){
                         std::option::Option::None => None,
-                        Some(c)=>{
-                            c.downcast_ref()
+                        Some(i)=>{
+                            let chip:&dyn pcb_rs::HardwareModule = &*self.chips[i];
+                            chip.downcast_ref()
                         }
                     }
                 }
 
                 pub fn get_chip_mut<'s,T:pcb_rs::HardwareModule>(&'s mut self,chip:&str)->std::option::Option<&'s mut T>{
-                    match self.chips.get_mut(chip){
+                    match self.chip_names.iter().position(|n|n == chip){
                         std::option::Option::None => None,
-                        Some(c)=>{
-                            c.downcast_mut()
+                        Some(i)=>{
+                            let chip:&mut dyn pcb_rs::HardwareModule = &mut *self.chips[i];
+                            chip.downcast_mut()
                         }
                     }
                 }
+
+                /// gives the contentions recorded in the last tick, if they were not taken already. The
+                /// contentions are only kept till the next tick, so they do not pile up in long simulations
+                pub fn take_contentions(&mut self)->std::vec::Vec<pcb_rs::Contention>{
+                    std::mem::take(&mut self.contentions)
+                }
+
+                #(#typed_chip_getters)*
             }
 
             impl pcb_rs::ChipInterface for #pcb_name{
@@ -688,124 +1217,166 @@ impl PcbMacroInput {
                     ret
                 }
                 
-                fn get_pin_value(&self, name: &str) -> std::option::Option<Box<dyn std::any::Any>>{
-                    match name{
-                        #(#ci_get_value),*
-                        _ => None
-                    }
+                fn get_pin_value(&self, name: &str) -> std::option::Option<pcb_rs::PinValue>{
+                    self.get_pin_value_at(self.get_pin_index(name)?)
                 }
-                
-                fn set_pin_value(&mut self, name: &str, val: &dyn std::any::Any){
-                    match name{
-                        #(#ci_set_value),*
-                        _ => {}
+
+                fn set_pin_value(&mut self, name: &str, val: &pcb_rs::PinValue) -> std::result::Result<(), pcb_rs::PinValueError>{
+                    match self.get_pin_index(name){
+                        std::option::Option::Some(i) => self.set_pin_value_at(i,val),
+                        std::option::Option::None => std::result::Result::Err(pcb_rs::PinValueError::UnknownPin(name.to_string()))
                     }
                 }
-                
+
                 fn is_pin_tristated(&self, name: &str) -> bool{
-                    match name{
-                        #(#ci_pin_tristated),*
-                        _ => false
+                    match self.get_pin_index(name){
+                        std::option::Option::Some(i) => self.is_pin_tristated_at(i),
+                        std::option::Option::None => false
                     }
                 }
-                
+
                 fn in_input_mode(&self, name: &str) -> bool{
+                    match self.get_pin_index(name){
+                        std::option::Option::Some(i) => self.in_input_mode_at(i),
+                        std::option::Option::None => false
+                    }
+                }
+
+                // a pcb made only of combinational chips settles them within its own tick,
+                // so it can itself be treated as a combinational chip by the encompassing pcb
+                fn is_combinational(&self) -> bool{
+                    self.chips.iter().all(|chip|chip.is_combinational())
+                }
+
+                fn pin_value_eq(&self, name: &str, val: &pcb_rs::PinValue) -> bool{
+                    match self.get_pin_index(name){
+                        std::option::Option::Some(i) => self.pin_value_eq_at(i,val),
+                        std::option::Option::None => false
+                    }
+                }
+
+                fn get_pin_index(&self, name: &str) -> std::option::Option<usize>{
                     match name{
-                        #(#ci_pin_input_mode),*
-                        _ => false
+                        #(#ci_pin_index,)*
+                        _ => std::option::Option::None
                     }
                 }
-            }
 
-            impl pcb_rs::Chip for #pcb_name{
-                fn tick(&mut self){
-                    use std::any::Any;
-                    use std::option::Option;
-                    use pcb_rs::{ChipPin,PinType,ConnectedPins,PinMetadata};
+                fn get_pin_value_at(&self, index: usize) -> std::option::Option<pcb_rs::PinValue>{
+                    // the shorted exposed pins must be of input type, shorting multiple o/p
+                    // is not valid and will error at building time,
+                    // and as all are shorted, we can just give 0th pin's value
+                    let __pin = self.exposed_pins.get(index)?[0];
+                    __pin.pin.get_value(self.chips[__pin.chip].as_ref())
+                }
 
+                fn set_pin_value_at(&mut self, index: usize, val: &pcb_rs::PinValue) -> std::result::Result<(), pcb_rs::PinValueError>{
+                    let __pins = match self.exposed_pins.get(index){
+                        std::option::Option::Some(pins) => pins,
+                        std::option::Option::None => return std::result::Result::Err(pcb_rs::PinValueError::UnknownPin(index.to_string()))
+                    };
+                    for __pin in __pins{
+                        let __chip = self.chips[__pin.chip].as_mut();
+                        if !__pin.pin.is_tristated(__chip){
+                            __pin.pin.set_value(__chip,val)?;
+                        }
+                    }
+                    std::result::Result::Ok(())
+                }
 
-                    for chip in self.chips.values_mut(){
-                        chip.tick();
+                fn is_pin_tristated_at(&self, index: usize) -> bool{
+                    // as the only pins to be grouped in expose are of input type,
+                    // it does not make sense to ask if the pins are tristated or not,
+                    // and when setting pins values, pins which are tristated are ignored anyways,
+                    // so we can just return false in that case
+                    match self.exposed_pins.get(index){
+                        std::option::Option::Some(pins) if pins.len() == 1 => {
+                            pins[0].pin.is_tristated(self.chips[pins[0].chip].as_ref())
+                        }
+                        _ => false
                     }
+                }
 
-                    for connection in &self.pin_connections{
-                        match connection{
-                            ConnectedPins::Pair{source,destination}=>{
-                                // because we have made sure the chips and pins exist properly,
-                                // we can unwrap directly
-                                // also this is simplest, as there is a single input and single output pin,
-                                // both of which are of respective types, so even if they're tristated,
-                                //  their data types will match, and there won't be an issue
-                                // TODO implement a test to verify this 
-                                let src = self.chips.get(source.chip).unwrap();
-                                let val = src.get_pin_value(source.pin).unwrap();
-                                // we have to take it as ref, otherwise the box is passed around,
-                                // instead of the data which we want
-                                let data_ref = val.as_ref();
-                                let dest = self.chips.get_mut(destination.chip).unwrap();
-                                dest.set_pin_value(destination.pin,data_ref);
-                            }
-                            ConnectedPins::Broadcast{source,destinations}=>{
-                                // now this can get tricky, as the source pin might be of type
-                                // io, so it can be present in destinations as well, so we have to skip it
-                                // as well as check that if there is any destination pin that is 
-                                // io type, then it is set to input mode
-                                // also we do not check if the source pin, if of io type
-                                // is set to input mode or not, the destination pins will get 
-                                // whatever its value is regardless
-                                let chip = self.chips.get(source.chip).unwrap();
-                                let val = chip.get_pin_value(source.pin).unwrap();
-                                let data_ref = val.as_ref();
-                                for dest in destinations{
-                                    if dest == source{
-                                        // accounts for the io type source pin
-                                        continue;
-                                    }
-                                    let chip = self.chips.get_mut(dest.chip).unwrap();
-                                    // we don't have to check if any other pin is of io type, because if it was
-                                    // then taht set-up would be in the tristated group
-                                    chip.set_pin_value(dest.pin,data_ref);
-                                }
-                            }
-                            ConnectedPins::Tristated{sources,destinations}=>{
-                                let mut val = Option::None;
-                                let mut active_chip = ChipPin{
-                                    chip: "unknown",
-                                    pin: "unknown",
-                                };
-                                for src in sources{
-                                    let chip = self.chips.get(src.chip).unwrap();
-                                    // input mode check if specifically for io pins, which would be present in
-                                    // both sources and destinations, and if one want to get the data in io pin
-                                    // the pin must not be in tristated mode, but must be in input mode
-                                    if !chip.in_input_mode(src.pin) && !chip.is_pin_tristated(src.pin){
-                                        if val.is_some(){
-                                            panic!("Multiple pins found active at the same time in a tristated group : pin {:?} and pin {:?} in group {:?}. Only one pin in a tristated group can be active at a time",src, active_chip,connection);
-                                        }
-                                        active_chip = *src;
-                                        val = Option::Some(chip.get_pin_value(src.pin).unwrap());
-                                    }
-                                }
-                                if let Some(val) = val{
-                                    let data_ref = val.as_ref();
-                                    for dest in destinations{
-                                        // skip in case the pin is io type and present in both source and destinations
-                                        if *dest == active_chip{
-                                            continue;
-                                        }
-                                        let chip = self.chips.get_mut(dest.chip).unwrap();
-                                        // skip tristated pins
-                                        if chip.is_pin_tristated(dest.pin){
-                                            continue;
-                                        }
-                                        chip.set_pin_value(dest.pin,data_ref);
-                                    }
-                                }
-                                
-                            }
+                fn in_input_mode_at(&self, index: usize) -> bool{
+                    match self.exposed_pins.get(index){
+                        std::option::Option::Some(pins) if pins.len() == 1 => {
+                            pins[0].pin.in_input_mode(self.chips[pins[0].chip].as_ref())
                         }
+                        // shorted exposed pins are all input type
+                        std::option::Option::Some(_) => true,
+                        std::option::Option::None => false
                     }
+                }
+
+                fn pin_value_eq_at(&self, index: usize, val: &pcb_rs::PinValue) -> bool{
+                    match self.exposed_pins.get(index){
+                        std::option::Option::Some(pins) => pins[0].pin.value_eq(self.chips[pins[0].chip].as_ref(),val),
+                        std::option::Option::None => false
+                    }
+                }
+
+                // all the pin values are stored in the chips, so resetting the chips also
+                // clears the values passed between them
+                fn reset(&mut self){
+                    for chip in &mut self.chips{
+                        chip.reset();
+                    }
+                    pcb_rs::clear_keepers(&mut self.resolved_connections);
+                    self.contentions.clear();
+                    self.cycle = 0;
+                }
+
+                fn save_state(&self) -> pcb_rs::ChipState{
+                    let mut state = pcb_rs::save_chips(&self.chip_names,&self.chips);
+                    pcb_rs::save_cycle(&mut state,&self.chip_clocks,self.cycle);
+                    state
+                }
+
+                fn restore_state(&mut self, state: &pcb_rs::ChipState) -> std::result::Result<(), pcb_rs::StateError>{
+                    if let std::option::Option::Some(cycle) = pcb_rs::restore_cycle(state)?{
+                        self.cycle = cycle;
+                    }
+                    pcb_rs::restore_chips(&self.chip_names,&mut self.chips,state)
+                }
+
+                fn get_chips(&self) -> std::vec::Vec<(&str, &dyn pcb_rs::HardwareModule)>{
+                    self.chip_names.iter().zip(&self.chips).map(|(name,chip)|->(&str,&dyn pcb_rs::HardwareModule){(name.as_str(),&**chip)}).collect()
+                }
+
+                fn get_pin_connections(&self) -> &[pcb_rs::ConnectedPins]{
+                    &self.pin_connections
+                }
+            }
+
+            impl pcb_rs::Chip for #pcb_name{
+                fn try_tick(&mut self)->std::result::Result<(),pcb_rs::SimError>{
+                    self.try_tick_edge(pcb_rs::ClockEdge::Rising)?;
+                    self.try_tick_edge(pcb_rs::ClockEdge::Falling)
+                }
+
+                // each edge is a half-cycle, where the chips are ticked and the values are passed on. Pcbs
+                // without chips using the falling edge do not have the second half, so their ticks stay the same
+                fn try_tick_edge(&mut self, edge: pcb_rs::ClockEdge)->std::result::Result<(),pcb_rs::SimError>{
+                    if edge == pcb_rs::ClockEdge::Falling && !self.falling_edge{
+                        return std::result::Result::Ok(());
+                    }
+                    // the contentions of the previous tick are dropped, the ones of the rising edge are kept
+                    // for the falling edge, so the contentions of the whole tick can be taken after it
+                    if edge == pcb_rs::ClockEdge::Rising{
+                        self.contentions.clear();
+                    }
+                    let start = self.contentions.len();
+                    // chips are only ticked on the ticks of their clocks, but the values are passed
+                    // on every tick, so chips of slower clocks hold their outputs between their ticks
+                    let cycle = pcb_rs::edge_cycle(&mut self.cycle,edge);
+                    pcb_rs::tick_chips(&mut self.chips,&self.chip_names,&self.chip_clocks,cycle,edge)?;
+                    pcb_rs::propagate_pin_values(&mut self.chips,&mut self.resolved_connections,&mut self.contentions,&mut self.scratch)?;
+                    pcb_rs::settle_combinational_chips(&mut self.chips,&self.chip_names,&mut self.resolved_connections,&self.combinational_chips,&mut self.contentions,&mut self.scratch)?;
+                    pcb_rs::contention_error(&self.contentions[start..])
+                }
 
+                fn uses_falling_edge(&self)->bool{
+                    self.falling_edge
                 }
             }
         }
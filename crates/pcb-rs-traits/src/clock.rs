@@ -0,0 +1,140 @@
+use super::*;
+
+/// This is the clock domain of a chip in a pcb. The chip is ticked on the ticks of the pcb whose cycle,
+/// counted from 0 since the pcb was built or reset, is `phase` more than a multiple of `divider`.
+/// The main clock of the pcb has divider 1 and phase 0, so its chips are ticked on every tick of the pcb
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Clock {
+    pub divider: u64,
+    pub phase: u64,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::MAIN
+    }
+}
+
+impl Clock {
+    /// the clock of the pcb ticks, which is the clock of chips not given any other clock
+    pub const MAIN: Clock = Clock {
+        divider: 1,
+        phase: 0,
+    };
+
+    /// gives a clock which ticks on every divider-th tick of this clock, starting from its tick
+    /// numbered phase, same as `clock slow = main / 4 phase 1;` in pcb!. Panics if the divider
+    /// is 0, or the phase is not less than the divider
+    pub fn divide(self, divider: u64, phase: u64) -> Clock {
+        if divider == 0 || phase >= divider {
+            panic!(
+                "clock divider must be more than 0 and more than the phase, found divider {} and phase {}",
+                divider, phase
+            );
+        }
+        Clock {
+            divider: self.divider * divider,
+            phase: self.phase + self.divider * phase,
+        }
+    }
+
+    /// checks if the chips of this clock are ticked on the given cycle of the pcb
+    pub fn ticks_at(&self, cycle: u64) -> bool {
+        cycle % self.divider == self.phase
+    }
+}
+
+/// This is an edge of the clock on which a chip is ticked. Each tick of a pcb is a clock cycle,
+/// made of the rising edge followed by the falling edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClockEdge {
+    Rising,
+    Falling,
+}
+
+/// gives the cycle on which the chips are ticked for the given edge, and counts the cycle for rising
+/// edges, used by the pcbs. The falling edge is of the same cycle as the rising edge before it
+pub fn edge_cycle(cycle: &mut u64, edge: ClockEdge) -> u64 {
+    match edge {
+        ClockEdge::Rising => {
+            *cycle += 1;
+            *cycle - 1
+        }
+        ClockEdge::Falling => cycle.saturating_sub(1),
+    }
+}
+
+// the pcbs which have chips of clocks other than the main clock store the cycle in their state,
+// so the clocks keep their phases after restoring. Pcbs without such chips do not need it
+
+const CYCLE_KEY: &str = "cycle";
+
+/// stores the cycle of a pcb in its saved state, if any of its chips are not of the main clock
+pub fn save_cycle(state: &mut ChipState, clocks: &[Clock], cycle: u64) {
+    if clocks.iter().any(|c| *c != Clock::MAIN) {
+        state
+            .state
+            .insert(CYCLE_KEY.to_string(), PinValue::U64(cycle));
+    }
+}
+
+/// gives the cycle stored in a saved state of a pcb, if any
+pub fn restore_cycle(state: &ChipState) -> Result<Option<u64>, StateError> {
+    match state.state.get(CYCLE_KEY) {
+        None => Ok(None),
+        Some(val) => match val.get::<u64>() {
+            Some(cycle) => Ok(Some(cycle)),
+            None => Err(StateError::InvalidValue {
+                path: CYCLE_KEY.to_string(),
+                error: PinValueError::TypeMismatch {
+                    pin: CYCLE_KEY,
+                    expected: std::any::type_name::<u64>(),
+                },
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falling_edge_is_of_same_cycle_as_rising_edge() {
+        let mut cycle = 0;
+        assert_eq!(edge_cycle(&mut cycle, ClockEdge::Rising), 0);
+        assert_eq!(edge_cycle(&mut cycle, ClockEdge::Falling), 0);
+        assert_eq!(edge_cycle(&mut cycle, ClockEdge::Rising), 1);
+        assert_eq!(edge_cycle(&mut cycle, ClockEdge::Falling), 1);
+        assert_eq!(cycle, 2);
+        // a falling edge before any rising edge is of the first cycle
+        assert_eq!(edge_cycle(&mut 0, ClockEdge::Falling), 0);
+    }
+
+    #[test]
+    fn divided_clocks_tick_on_their_phase() {
+        let clock = Clock::MAIN.divide(3, 2);
+        let ticks: Vec<_> = (0..9).filter(|c| clock.ticks_at(*c)).collect();
+        assert_eq!(ticks, [2, 5, 8]);
+        assert!((0..4).all(|c| Clock::default().ticks_at(c)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn phase_must_be_less_than_divider() {
+        Clock::MAIN.divide(2, 2);
+    }
+
+    #[test]
+    fn cycle_is_saved_only_for_clock_domains() {
+        let mut state = ChipState::default();
+        save_cycle(&mut state, &[Clock::MAIN], 5);
+        assert_eq!(restore_cycle(&state).unwrap(), None);
+        save_cycle(&mut state, &[Clock::MAIN, Clock::MAIN.divide(2, 0)], 5);
+        assert_eq!(restore_cycle(&state).unwrap(), Some(5));
+        state
+            .state
+            .insert(CYCLE_KEY.to_string(), PinValue::Bool(true));
+        assert!(restore_cycle(&state).is_err());
+    }
+}
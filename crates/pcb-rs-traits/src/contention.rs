@@ -0,0 +1,158 @@
+use super::*;
+
+/// This decides what the pcb does when multiple pins of a tristated group are active at the
+/// same time, i.e. on a bus contention. It can be set for all the tristated groups of a pcb,
+/// or for a single group, given by any of its pins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContentionPolicy {
+    /// panics, equivalent to the chips burning in real hardware, and try_tick of the pcb gives the
+    /// contention as an error. This is the default. Logic pins still resolve to X, as they can represent the contention
+    #[default]
+    Panic,
+    /// same as Unknown, and try_tick of the pcb gives the contention as an error
+    Error,
+    /// records the contention and drives an unknown value : the conflicting levels of logic pins
+    /// are X, and pins of other types keep their previous values, as they do not have an unknown value
+    Unknown,
+    /// drives the bitwise and of the active values, as an open-drain bus with a pull-up would
+    WiredAnd,
+    /// drives the bitwise or of the active values, as an open-source bus with a pull-down would
+    WiredOr,
+}
+
+/// This is a contention recorded by the pcb, with the Error and Unknown policies,
+/// or given as an error with the Panic policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contention {
+    /// the pins which were active at the same time
+    pub drivers: Vec<ChipPin>,
+    /// the policy of the group of the pins
+    pub policy: ContentionPolicy,
+}
+
+impl std::fmt::Display for Contention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pins: Vec<_> = self
+            .drivers
+            .iter()
+            .map(|p| format!("{}::{}", p.chip, p.pin))
+            .collect();
+        write!(
+            f,
+            "Multiple pins found active at the same time in a tristated group : {}. Only one pin in a tristated group can be active at a time",
+            pins.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for Contention {}
+
+/// Sets the contention policies of the tristated groups : a group gets the policy given for any of
+/// its pins, where later policies override earlier ones, or the default policy. Pins which are not
+/// in any tristated group cannot have contentions, so an error is given for those. The wired policies
+/// can only be used for groups whose pins have bitwise values, so an error is given for the others
+pub fn set_contention_policies(
+    connections: &mut [ResolvedPins],
+    chips: &[Box<PcbChip>],
+    default: ContentionPolicy,
+    policies: &[(ChipPin, ContentionPolicy)],
+    errors: &mut Vec<BuildError>,
+) {
+    for connection in connections.iter_mut() {
+        if let ResolvedPins::Tristated { policy, .. } = connection {
+            *policy = default;
+        }
+    }
+    for (pin, p) in policies {
+        let group = connections.iter_mut().find_map(|c| match c {
+            ResolvedPins::Tristated {
+                sources,
+                destinations,
+                policy,
+                ..
+            } if sources
+                .iter()
+                .chain(destinations.iter())
+                .any(|s| s.name == *pin) =>
+            {
+                Some(policy)
+            }
+            _ => None,
+        });
+        match group {
+            Some(policy) => *policy = *p,
+            None => errors.push(BuildError::NotTristated(*pin)),
+        }
+    }
+    for connection in connections.iter() {
+        if let ResolvedPins::Tristated {
+            sources, policy, ..
+        } = connection
+        {
+            if !matches!(
+                policy,
+                ContentionPolicy::WiredAnd | ContentionPolicy::WiredOr
+            ) {
+                continue;
+            }
+            // the pins of types other than bool, integers, bit vectors and logic give custom
+            // values, which do not have bits to combine
+            let custom = sources.iter().find(|s| {
+                matches!(
+                    chips[s.chip].get_pin_value(s.name.pin),
+                    Some(PinValue::Custom(_))
+                )
+            });
+            if let Some(s) = custom {
+                let md = chips[s.chip].get_pin_list()[s.name.pin];
+                errors.push(BuildError::NotCombinable {
+                    pin: (s.name, md),
+                    policy: *policy,
+                });
+            }
+        }
+    }
+}
+
+/// gives the first of the contentions with the Error policy as an error, used by the pcbs
+/// to give the contentions recorded in a tick from try_tick
+pub fn contention_error(contentions: &[Contention]) -> Result<(), SimError> {
+    match contentions
+        .iter()
+        .find(|c| c.policy == ContentionPolicy::Error)
+    {
+        Some(c) => Err(SimError::Contention {
+            path: String::new(),
+            contention: c.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+// gives the value of the bus driven by both the values, as per the policy. For the policies
+// which do not combine the values, gives None if the values cannot be resolved either. The wired
+// policies are checked at build, but manually implemented chips can still give other values
+// when ticking, so those give an error
+pub(crate) fn combine(
+    policy: ContentionPolicy,
+    v1: &PinValue,
+    v2: &PinValue,
+    drivers: impl Iterator<Item = ChipPin>,
+) -> Result<Option<PinValue>, SimError> {
+    let combined = match policy {
+        ContentionPolicy::WiredAnd => v1.wired_and(v2),
+        ContentionPolicy::WiredOr => v1.wired_or(v2),
+        _ => return Ok(v1.resolve(v2)),
+    };
+    match combined {
+        Some(combined) => Ok(Some(combined)),
+        None => Err(SimError::NotCombinable {
+            path: String::new(),
+            contention: Contention {
+                drivers: drivers.collect(),
+                policy,
+            },
+        }),
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+
+/// This describes a pin of a chip type, as known when compiling. Unlike PinMetadata, this does not
+/// have the data type of the pin, as TypeId and type_name cannot be used in const contexts
+#[derive(Debug, Clone, Copy)]
+pub struct PinDescriptor {
+    pub name: &'static str,
+    pub pin_type: PinType,
+    pub tristatable: bool,
+}
+
+/// This gives the pins of a chip type as a constant, so that pcb! can check the pins of chips declared
+/// with a type when compiling. This is implemented by the Chip derive, and for the pcb! generated pcbs,
+/// so pcbs can be typed chips of other pcbs as well. Chips which implement the traits manually do not
+/// need to implement this, their pins are checked when building the pcb instead
+pub trait ChipPins {
+    const PINS: &'static [PinDescriptor];
+
+    /// false when the pins are not known when compiling, in which case PINS is not used. The pcbs
+    /// give false when any of their exposed pins is of a chip whose pins are not known
+    const KNOWN: bool = true;
+}
+
+// pcb! needs the pins of the typed chips if they are known, but cannot know if a type implements
+// ChipPins. So the pins are taken as PinsOf::<T>::PINS, which is the inherent const when T implements
+// ChipPins, and the const of the NoChipPins trait otherwise, as inherent items are preferred over the
+// ones of traits. This only works for concrete types, which the typed chips are
+
+/// gives the pins of the chip type T as PinsOf::<T>::PINS, if they are known. Used by pcb!
+#[doc(hidden)]
+pub struct PinsOf<T: ?Sized>(std::marker::PhantomData<T>);
+
+impl<T: ChipPins + ?Sized> PinsOf<T> {
+    pub const PINS: Option<&'static [PinDescriptor]> = if T::KNOWN { Some(T::PINS) } else { None };
+}
+
+/// gives None as the pins of chip types which do not implement ChipPins, see PinsOf
+#[doc(hidden)]
+pub trait NoChipPins {
+    const PINS: Option<&'static [PinDescriptor]> = None;
+}
+
+impl<T: ?Sized> NoChipPins for PinsOf<T> {}
+
+impl PinDescriptor {
+    /// same as PinMetadata::is_connectable, except the data types are not compared,
+    /// those are checked when building the pcb
+    pub const fn is_connectable(&self, other: &PinDescriptor) -> bool {
+        let both_input =
+            matches!(self.pin_type, PinType::Input) && matches!(other.pin_type, PinType::Input);
+        let both_output =
+            matches!(self.pin_type, PinType::Output) && matches!(other.pin_type, PinType::Output);
+        !both_input && !both_output && self.tristatable == other.tristatable
+    }
+}
+
+/// gives the pin of the given name, if any. This is a const fn, so pcb! can use it in const assertions
+pub const fn find_pin(pins: &[PinDescriptor], name: &str) -> Option<PinDescriptor> {
+    let mut i = 0;
+    while i < pins.len() {
+        if str_eq(pins[i].name, name) {
+            return Some(pins[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// gives the pin exposed as as_name by a pcb, which is the given pin of its chip. Used by pcb! for the
+/// pins of the pcbs, where the pins of the chip may not be known, in which case the pins of the pcb are
+/// not known either, and the pin given here is not used
+#[doc(hidden)]
+pub const fn exposed_pin(
+    pins: Option<&[PinDescriptor]>,
+    pin: &str,
+    as_name: &'static str,
+) -> PinDescriptor {
+    match pins {
+        Some(pins) => match find_pin(pins, pin) {
+            Some(p) => PinDescriptor { name: as_name, ..p },
+            None => panic!("exposed pin not found in the pins of its chip"),
+        },
+        None => PinDescriptor {
+            name: as_name,
+            pin_type: PinType::Input,
+            tristatable: false,
+        },
+    }
+}
+
+// str comparison is not available in const fns yet, so we compare the bytes
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
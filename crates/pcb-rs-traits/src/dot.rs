@@ -0,0 +1,192 @@
+use super::*;
+use std::fmt::Write;
+
+// colours of the pin ports, by their type
+fn pin_colour(pin_type: PinType) -> &'static str {
+    match pin_type {
+        PinType::Input => "lightblue",
+        PinType::Output => "lightsalmon",
+        PinType::IO => "palegreen",
+    }
+}
+
+// data type names can have <> in them, which need to be escaped in html labels
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// ids of chips are their path from the top pcb, such as `inner/c1`
+fn chip_id(path: &str, chip: &str) -> String {
+    if path.is_empty() {
+        chip.to_string()
+    } else {
+        format!("{}/{}", path, chip)
+    }
+}
+
+fn port(path: &str, pin: &ChipPin) -> String {
+    format!("\"{}\":\"{}\"", chip_id(path, pin.chip), pin.pin)
+}
+
+// writes a node with one port per pin, the header being the chip name
+fn write_chip_node<C: ChipInterface + ?Sized>(out: &mut String, id: &str, name: &str, chip: &C) {
+    let mut pins: Vec<_> = chip.get_pin_list().into_iter().collect();
+    pins.sort_by_key(|(name, _)| *name);
+    let _ = write!(
+        out,
+        "\"{}\" [label=<<TABLE BORDER=\"0\" CELLBORDER=\"1\" CELLSPACING=\"0\"><TR><TD BGCOLOR=\"lightgrey\"><B>{}</B></TD></TR>",
+        id,
+        escape(name)
+    );
+    for (pin, md) in pins {
+        let _ = write!(
+            out,
+            "<TR><TD PORT=\"{}\" BGCOLOR=\"{}\">{} : {}</TD></TR>",
+            pin,
+            pin_colour(md.pin_type),
+            pin,
+            escape(md.data_type)
+        );
+    }
+    let _ = writeln!(out, "</TABLE>>];");
+}
+
+fn write_connections(out: &mut String, path: &str, connections: &[ConnectedPins]) {
+    for (i, connection) in connections.iter().enumerate() {
+        match connection {
+            ConnectedPins::Pair {
+                source,
+                destination,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "{} -> {};",
+                    port(path, source),
+                    port(path, destination)
+                );
+            }
+            ConnectedPins::Broadcast {
+                source,
+                destinations,
+            } => {
+                for dest in destinations {
+                    // the source can be an io pin which is also in destinations
+                    if dest == source {
+                        continue;
+                    }
+                    let _ = writeln!(out, "{} -> {};", port(path, source), port(path, dest));
+                }
+            }
+            ConnectedPins::Tristated {
+                sources,
+                destinations,
+            } => {
+                // the bus is shown as a hub node, with the io pins connected both ways. The # in id
+                // makes sure it does not clash with any chip name
+                let hub = format!("\"{}\"", chip_id(path, &format!("#bus{}", i)));
+                let _ = writeln!(out, "{} [shape=diamond, label=\"bus\"];", hub);
+                for src in sources {
+                    let dir = if destinations.contains(src) {
+                        " [dir=both]"
+                    } else {
+                        ""
+                    };
+                    let _ = writeln!(out, "{} -> {}{};", port(path, src), hub, dir);
+                }
+                for dest in destinations.iter().filter(|d| !sources.contains(d)) {
+                    let _ = writeln!(out, "{} -> {};", hub, port(path, dest));
+                }
+            }
+        }
+    }
+}
+
+fn write_chips<C: ChipInterface + ?Sized>(out: &mut String, path: &str, module: &C) {
+    let mut chips = module.get_chips();
+    chips.sort_by_key(|(name, _)| *name);
+    for (name, chip) in chips {
+        let id = chip_id(path, name);
+        if chip.get_chips().is_empty() {
+            write_chip_node(out, &id, name, chip);
+            continue;
+        }
+        // nested pcbs are shown as clusters, with their exposed pins as a node in it,
+        // so the connections to the pcb can be shown
+        let _ = writeln!(out, "subgraph \"cluster_{}\" {{", id);
+        let _ = writeln!(out, "label=\"{}\";", name);
+        if !chip.get_pin_list().is_empty() {
+            write_chip_node(out, &id, name, chip);
+        }
+        write_chips(out, &id, chip);
+        let _ = writeln!(out, "}}");
+    }
+    write_connections(out, path, module.get_pin_connections());
+}
+
+/// gives the Graphviz DOT graph of the chips in the module and their connections,
+/// see ChipInterface::to_dot
+pub fn to_dot<C: ChipInterface + ?Sized>(module: &C) -> String {
+    let mut out = String::new();
+    out.push_str("digraph pcb {\nrankdir=LR;\nnode [shape=plaintext];\n");
+    write_chips(&mut out, "", module);
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(chip: &'static str, pin: &'static str) -> ChipPin {
+        ChipPin { chip, pin }
+    }
+
+    #[test]
+    fn nested_chips_have_path_ids() {
+        assert_eq!(chip_id("", "c1"), "c1");
+        assert_eq!(chip_id("inner/c1", "c2"), "inner/c1/c2");
+        assert_eq!(escape("Option<u8>"), "Option&lt;u8&gt;");
+    }
+
+    #[test]
+    fn groups_are_edges() {
+        let mut out = String::new();
+        let connections = [
+            ConnectedPins::Pair {
+                source: pin("c1", "out"),
+                destination: pin("c2", "in"),
+            },
+            ConnectedPins::Broadcast {
+                source: pin("c1", "io"),
+                destinations: vec![pin("c1", "io"), pin("c3", "in")],
+            },
+        ];
+        write_connections(&mut out, "top", &connections);
+        assert_eq!(
+            out,
+            "\"top/c1\":\"out\" -> \"top/c2\":\"in\";\n\"top/c1\":\"io\" -> \"top/c3\":\"in\";\n"
+        );
+    }
+
+    #[test]
+    fn tristated_group_is_hub_node() {
+        let mut out = String::new();
+        let connections = [ConnectedPins::Tristated {
+            sources: vec![pin("c1", "out"), pin("c2", "io")],
+            destinations: vec![pin("c2", "io"), pin("c3", "in")],
+        }];
+        write_connections(&mut out, "", &connections);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "\"#bus0\" [shape=diamond, label=\"bus\"];",
+                "\"c1\":\"out\" -> \"#bus0\";",
+                "\"c2\":\"io\" -> \"#bus0\" [dir=both];",
+                "\"#bus0\" -> \"c3\":\"in\";",
+            ]
+        );
+    }
+}
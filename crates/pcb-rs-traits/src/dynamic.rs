@@ -0,0 +1,444 @@
+use super::*;
+
+/// This is the runtime counterpart of the builder generated by the pcb! macro, for
+/// when the chips and their connections are only known at runtime. The chips are added
+/// and connected using the functions of this, and build() does the same validations as
+/// the pcb! generated builder. The chip and pin names in ChipPin are 'static, so names which
+/// are only known at runtime must be leaked or otherwise made 'static before connecting
+#[derive(Default)]
+pub struct DynamicPcbBuilder {
+    added_chip_map: HashMap<String, Box<PcbChip>>,
+    // chip names in order of adding, along with their tick priority
+    chip_priorities: Vec<(String, i32)>,
+    // clocks of the chips which are not of the main clock
+    chip_clocks: Vec<(&'static str, Clock)>,
+    connections: Vec<(ChipPin, ChipPin)>,
+    exposed_pins: Vec<(Vec<ChipPin>, &'static str)>,
+    contention_policy: ContentionPolicy,
+    contention_policies: Vec<(ChipPin, ContentionPolicy)>,
+    pulls: Vec<(ChipPin, Pull)>,
+}
+
+/// This is the pcb built by the DynamicPcbBuilder. It behaves the same as the pcb generated
+/// by the pcb! macro, and thus can be used in it as a chip, and vice-versa
+pub struct DynamicPcb {
+    // chips are stored in the order they are ticked, and chip_names has their names in the same order
+    chip_names: Vec<String>,
+    chips: Vec<Box<PcbChip>>,
+    pin_connections: Vec<ConnectedPins>,
+    resolved_connections: Vec<ResolvedPins>,
+    combinational_chips: Vec<(usize, Vec<PinIndex>)>,
+    exposed_pins: Vec<(Vec<PinSlot>, &'static str)>,
+    // clocks of the chips, in the same order as chips, and the number of ticks since building or reset
+    chip_clocks: Vec<Clock>,
+    cycle: u64,
+    // if any of the chips use the falling edge, in which case the pcb drives both the edges
+    falling_edge: bool,
+    // contentions recorded with Error and Unknown policies in the last tick, till they are taken
+    contentions: Vec<Contention>,
+    scratch: ScratchBuffers,
+}
+
+impl DynamicPcbBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a chip to the pcb, the name is the one used in the ChipPins to connect it.
+    /// Chips are ticked in the order they are added
+    pub fn add_chip(self, name: &str, chip: Box<PcbChip>) -> Self {
+        self.add_chip_with_priority(name, 0, chip)
+    }
+
+    /// adds a chip with the given tick priority, same as `chip c1 priority 2;` in pcb!.
+    /// Chips with higher priority are ticked first, and chips added using add_chip have priority 0
+    pub fn add_chip_with_priority(
+        mut self,
+        name: &str,
+        priority: i32,
+        chip: Box<PcbChip>,
+    ) -> Self {
+        self.chip_priorities.retain(|(n, _)| n != name);
+        self.chip_priorities.push((name.to_string(), priority));
+        self.added_chip_map.insert(name.to_string(), chip);
+        self
+    }
+
+    /// sets the clock of the chip, same as `chip c1 @ slow;` in pcb!. Chips which are not given
+    /// a clock are of the main clock, and ticked on every tick of the pcb
+    pub fn clock(mut self, chip: &'static str, clock: Clock) -> Self {
+        self.chip_clocks.retain(|(c, _)| *c != chip);
+        self.chip_clocks.push((chip, clock));
+        self
+    }
+
+    /// connects two pins, same as `c1::p1 - c2::p2;` in pcb!
+    pub fn connect(mut self, pin1: ChipPin, pin2: ChipPin) -> Self {
+        // connecting same pins twice does not change anything, so we only keep it once
+        if !self
+            .connections
+            .iter()
+            .any(|c| *c == (pin1, pin2) || *c == (pin2, pin1))
+        {
+            self.connections.push((pin1, pin2));
+        }
+        self
+    }
+
+    /// exposes the pins as a single pin of the pcb, same as `expose c1::p1,c2::p2 as p3;` in pcb!
+    pub fn expose(mut self, pins: &[ChipPin], as_name: &'static str) -> Self {
+        self.exposed_pins.push((pins.to_vec(), as_name));
+        self
+    }
+
+    /// sets the contention policy for all the tristated groups, same as `contention unknown;` in pcb!
+    pub fn contention_policy(mut self, policy: ContentionPolicy) -> Self {
+        self.contention_policy = policy;
+        self
+    }
+
+    /// sets the contention policy for the tristated group of the given pin, same as
+    /// `contention c1::p1 = wired_and;` in pcb!
+    pub fn bus_contention_policy(mut self, pin: ChipPin, policy: ContentionPolicy) -> Self {
+        self.contention_policies.push((pin, policy));
+        self
+    }
+
+    /// sets the pull of the tristated group of the given pin, same as `pull c1::p1 = 0xFF_u8;`
+    /// or `keep c1::p1;` in pcb!
+    pub fn pull(mut self, pin: ChipPin, pull: Pull) -> Self {
+        self.pulls.push((pin, pull));
+        self
+    }
+
+    /// validates the added chips and connections, and gives the pcb, or all the errors found
+    pub fn build(self) -> Result<DynamicPcb, Vec<BuildError>> {
+        let mut errors = Vec::new();
+        // this is checked at compile time by pcb!
+        self.check_declaration(&mut errors);
+        let used_pins = used_pins(
+            &self.connections,
+            &self.exposed_pins,
+            &self.contention_policies,
+            &self.pulls,
+        );
+        let clock_chips: Vec<_> = self.chip_clocks.iter().map(|(chip, _)| *chip).collect();
+        check_added_chips(&self.added_chip_map, &clock_chips, &used_pins, &mut errors);
+        check_valid_chips(
+            &self.added_chip_map,
+            &used_pins,
+            &self.chip_clocks,
+            &mut errors,
+        );
+        // rest of the checks need all the chips and their pins to be present,
+        // so we cannot go further if they are not
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let pin_metadata = get_pin_metadata(&self.added_chip_map, &used_pins);
+        check_pin_connections(&self.connections, &pin_metadata, &mut errors);
+        let shorted_pins = get_short_pin_set(&self.connections);
+        check_exposed_pins(&self.exposed_pins, &shorted_pins, &pin_metadata, &mut errors);
+        let pin_connections = get_connected_pins(&shorted_pins, &pin_metadata, &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        // this is a stable sort, so the order of adding is kept for chips with same priority
+        let mut chip_priorities = self.chip_priorities;
+        chip_priorities.sort_by_key(|(_, priority)| std::cmp::Reverse(*priority));
+        let tick_order: Vec<_> = chip_priorities.into_iter().map(|(name, _)| name).collect();
+        let (chip_names, chips) = into_chip_slots(self.added_chip_map, &tick_order, &mut errors);
+        let chip_clocks = chip_names
+            .iter()
+            .map(|name| {
+                self.chip_clocks
+                    .iter()
+                    .find(|(c, _)| c == name)
+                    .map_or(Clock::MAIN, |(_, clock)| *clock)
+            })
+            .collect();
+        let mut resolved_connections = resolve_connections(&chip_names, &chips, &pin_connections);
+        set_contention_policies(
+            &mut resolved_connections,
+            &chips,
+            self.contention_policy,
+            &self.contention_policies,
+            &mut errors,
+        );
+        set_pulls(&mut resolved_connections, &chips, self.pulls, &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let combinational_chips = get_combinational_chips(&chips);
+        let falling_edge = chips.iter().any(|chip| chip.uses_falling_edge());
+        let exposed_pins = self
+            .exposed_pins
+            .into_iter()
+            .map(|(pins, as_name)| {
+                let pins = pins
+                    .into_iter()
+                    .map(|pin| resolve_pin(&chip_names, &chips, pin))
+                    .collect();
+                (pins, as_name)
+            })
+            .collect();
+
+        Ok(DynamicPcb {
+            chip_names,
+            chips,
+            pin_connections,
+            resolved_connections,
+            combinational_chips,
+            exposed_pins,
+            chip_clocks,
+            cycle: 0,
+            falling_edge,
+            contentions: Vec::new(),
+            scratch: ScratchBuffers::default(),
+        })
+    }
+
+    fn check_declaration(&self, errors: &mut Vec<BuildError>) {
+        if self.added_chip_map.is_empty() {
+            errors.push(BuildError::NoChips);
+        }
+        for (pin1, pin2) in &self.connections {
+            if pin1 == pin2 {
+                errors.push(BuildError::SelfConnection(*pin1));
+            }
+        }
+        let mut exposed: Vec<(ChipPin, &'static str)> = Vec::new();
+        let mut as_names: Vec<&'static str> = Vec::new();
+        for (pins, as_name) in &self.exposed_pins {
+            if pins.is_empty() {
+                errors.push(BuildError::ExposedNoPins(as_name));
+            }
+            if as_names.contains(as_name) {
+                errors.push(BuildError::ExposedNameReused(as_name));
+            } else {
+                as_names.push(as_name);
+            }
+            for pin in pins {
+                match exposed.iter().find(|(p, _)| p == pin) {
+                    Some((_, previous)) => errors.push(BuildError::ExposedMultipleTimes {
+                        pin: *pin,
+                        as_names: (previous, as_name),
+                    }),
+                    None => exposed.push((*pin, as_name)),
+                }
+            }
+        }
+    }
+}
+
+impl DynamicPcb {
+    fn get_slot(&self, chip: &str) -> Option<usize> {
+        self.chip_names.iter().position(|n| n == chip)
+    }
+
+    // the chips are upcast to HardwareModule for downcasting, as they are SendHardwareModule with the `parallel` feature
+
+    pub fn get_chip<T: HardwareModule>(&self, chip: &str) -> Option<&T> {
+        let slot = self.get_slot(chip)?;
+        let chip: &dyn HardwareModule = &*self.chips[slot];
+        chip.downcast_ref()
+    }
+
+    pub fn get_chip_mut<T: HardwareModule>(&mut self, chip: &str) -> Option<&mut T> {
+        let slot = self.get_slot(chip)?;
+        let chip: &mut dyn HardwareModule = &mut *self.chips[slot];
+        chip.downcast_mut()
+    }
+
+    /// gives the contentions recorded in the last tick, if they were not taken already. The
+    /// contentions are only kept till the next tick, so they do not pile up in long simulations
+    pub fn take_contentions(&mut self) -> Vec<Contention> {
+        std::mem::take(&mut self.contentions)
+    }
+}
+
+// the shorted exposed pins are all of input type and of same data type, which is verified
+// when building, so for most of these we can just use the 0th pin
+impl ChipInterface for DynamicPcb {
+    fn get_pin_list(&self) -> HashMap<&'static str, PinMetadata> {
+        self.exposed_pins
+            .iter()
+            .map(|(pins, as_name)| {
+                let md = self.chips[pins[0].chip].get_pin_list()[pins[0].name.pin];
+                (*as_name, md)
+            })
+            .collect()
+    }
+
+    fn get_pin_value(&self, name: &str) -> Option<PinValue> {
+        self.get_pin_value_at(self.get_pin_index(name)?)
+    }
+
+    fn set_pin_value(&mut self, name: &str, val: &PinValue) -> Result<(), PinValueError> {
+        match self.get_pin_index(name) {
+            Some(i) => self.set_pin_value_at(i, val),
+            None => Err(PinValueError::UnknownPin(name.to_string())),
+        }
+    }
+
+    fn is_pin_tristated(&self, name: &str) -> bool {
+        match self.get_pin_index(name) {
+            Some(i) => self.is_pin_tristated_at(i),
+            None => false,
+        }
+    }
+
+    fn in_input_mode(&self, name: &str) -> bool {
+        match self.get_pin_index(name) {
+            Some(i) => self.in_input_mode_at(i),
+            None => false,
+        }
+    }
+
+    fn is_combinational(&self) -> bool {
+        self.chips.iter().all(|chip| chip.is_combinational())
+    }
+
+    fn pin_value_eq(&self, name: &str, val: &PinValue) -> bool {
+        match self.get_pin_index(name) {
+            Some(i) => self.pin_value_eq_at(i, val),
+            None => false,
+        }
+    }
+
+    // the index of exposed pin is its position in exposed_pins
+
+    fn get_pin_index(&self, name: &str) -> Option<usize> {
+        self.exposed_pins
+            .iter()
+            .position(|(_, as_name)| *as_name == name)
+    }
+
+    fn get_pin_value_at(&self, index: usize) -> Option<PinValue> {
+        let pin = self.exposed_pins.get(index)?.0[0];
+        pin.pin.get_value(self.chips[pin.chip].as_ref())
+    }
+
+    fn set_pin_value_at(&mut self, index: usize, val: &PinValue) -> Result<(), PinValueError> {
+        let pins = match self.exposed_pins.get(index) {
+            Some((pins, _)) => pins,
+            None => return Err(PinValueError::UnknownPin(index.to_string())),
+        };
+        for pin in pins {
+            let chip = self.chips[pin.chip].as_mut();
+            if !pin.pin.is_tristated(chip) {
+                pin.pin.set_value(chip, val)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_pin_tristated_at(&self, index: usize) -> bool {
+        match self.exposed_pins.get(index) {
+            Some((pins, _)) if pins.len() == 1 => {
+                pins[0].pin.is_tristated(self.chips[pins[0].chip].as_ref())
+            }
+            _ => false,
+        }
+    }
+
+    fn in_input_mode_at(&self, index: usize) -> bool {
+        match self.exposed_pins.get(index) {
+            Some((pins, _)) if pins.len() == 1 => {
+                pins[0].pin.in_input_mode(self.chips[pins[0].chip].as_ref())
+            }
+            // shorted exposed pins are all input type
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn pin_value_eq_at(&self, index: usize, val: &PinValue) -> bool {
+        match self.exposed_pins.get(index) {
+            Some((pins, _)) => pins[0].pin.value_eq(self.chips[pins[0].chip].as_ref(), val),
+            None => false,
+        }
+    }
+
+    fn reset(&mut self) {
+        for chip in &mut self.chips {
+            chip.reset();
+        }
+        clear_keepers(&mut self.resolved_connections);
+        self.contentions.clear();
+        self.cycle = 0;
+    }
+
+    fn save_state(&self) -> ChipState {
+        let mut state = save_chips(&self.chip_names, &self.chips);
+        save_cycle(&mut state, &self.chip_clocks, self.cycle);
+        state
+    }
+
+    fn restore_state(&mut self, state: &ChipState) -> Result<(), StateError> {
+        if let Some(cycle) = restore_cycle(state)? {
+            self.cycle = cycle;
+        }
+        restore_chips(&self.chip_names, &mut self.chips, state)
+    }
+
+    fn get_chips(&self) -> Vec<(&str, &dyn HardwareModule)> {
+        self.chip_names
+            .iter()
+            .zip(&self.chips)
+            .map(|(name, chip)| -> (&str, &dyn HardwareModule) { (name.as_str(), &**chip) })
+            .collect()
+    }
+
+    fn get_pin_connections(&self) -> &[ConnectedPins] {
+        &self.pin_connections
+    }
+}
+
+impl Chip for DynamicPcb {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.try_tick_edge(ClockEdge::Rising)?;
+        self.try_tick_edge(ClockEdge::Falling)
+    }
+
+    // each edge is a half-cycle, where the chips are ticked and the values are passed on. Pcbs
+    // without chips using the falling edge do not have the second half, so their ticks stay the same
+    fn try_tick_edge(&mut self, edge: ClockEdge) -> Result<(), SimError> {
+        if edge == ClockEdge::Falling && !self.falling_edge {
+            return Ok(());
+        }
+        // the contentions of the previous tick are dropped, the ones of the rising edge are kept
+        // for the falling edge, so the contentions of the whole tick can be taken after it
+        if edge == ClockEdge::Rising {
+            self.contentions.clear();
+        }
+        let start = self.contentions.len();
+        let cycle = edge_cycle(&mut self.cycle, edge);
+        tick_chips(
+            &mut self.chips,
+            &self.chip_names,
+            &self.chip_clocks,
+            cycle,
+            edge,
+        )?;
+        propagate_pin_values(
+            &mut self.chips,
+            &mut self.resolved_connections,
+            &mut self.contentions,
+            &mut self.scratch,
+        )?;
+        settle_combinational_chips(
+            &mut self.chips,
+            &self.chip_names,
+            &mut self.resolved_connections,
+            &self.combinational_chips,
+            &mut self.contentions,
+            &mut self.scratch,
+        )?;
+        contention_error(&self.contentions[start..])
+    }
+
+    fn uses_falling_edge(&self) -> bool {
+        self.falling_edge
+    }
+}
@@ -0,0 +1,287 @@
+use super::*;
+
+#[derive(Debug, Clone)]
+/// This is the error given when building a pcb fails, and carries the
+/// pins and their metadata which caused the failure
+pub enum BuildError {
+    /// the pcb does not have any chips
+    NoChips,
+    /// a pin is connected to itself
+    SelfConnection(ChipPin),
+    /// a pin is exposed as more than one pin of the pcb
+    ExposedMultipleTimes {
+        pin: ChipPin,
+        as_names: (&'static str, &'static str),
+    },
+    /// no pins are given to expose as the pin of the given name
+    ExposedNoPins(&'static str),
+    /// more than one pin, or group of shorted pins, is exposed as the pin of the given name
+    ExposedNameReused(&'static str),
+    /// pins exposed shorted with each other are also connected to other pins
+    ExposedShortConnected {
+        as_name: &'static str,
+        pins: Vec<ChipPin>,
+    },
+    /// a chip declared in the pcb was not added to the builder
+    MissingChip(&'static str),
+    /// a chip was added to the builder using add_chip, under a name which is not declared in the pcb
+    UndeclaredChip(String),
+    /// a chip declared with a type in the pcb was added by name, and is of a different type
+    ChipTypeMismatch {
+        chip: &'static str,
+        expected: &'static str,
+    },
+    /// an added chip does not have a pin which is connected or exposed in the pcb
+    UnknownPin(ChipPin),
+    /// two pins are connected, but are not connectable, see PinMetadata::is_connectable
+    IncompatiblePins {
+        first: (ChipPin, PinMetadata),
+        second: (ChipPin, PinMetadata),
+    },
+    /// a group of shorted pins has multiple output pins, but not all of its pins are tristatable
+    MultipleDrivers(Vec<(ChipPin, PinMetadata)>),
+    /// a group of shorted pins has both tristatable and non-tristatable pins
+    MixedTristate(Vec<(ChipPin, PinMetadata)>),
+    /// a pin exposed shorted with other pins is not of input type
+    ExposedShortNotInput {
+        as_name: &'static str,
+        pin: (ChipPin, PinMetadata),
+    },
+    /// a pin exposed shorted with other pins is not of same data type as the first of those pins
+    ExposedShortTypeMismatch {
+        as_name: &'static str,
+        pin: (ChipPin, PinMetadata),
+        expected: (ChipPin, PinMetadata),
+    },
+    /// a contention policy or pull is given for a pin which is not in a tristated group
+    NotTristated(ChipPin),
+    /// the pull value given for a pin cannot be given to it, as it is of a different data type
+    PullTypeMismatch {
+        pin: (ChipPin, PinMetadata),
+        data_type: &'static str,
+    },
+    /// a combinational chip is given a clock other than the main clock, but combinational chips
+    /// are settled on every tick of the pcb
+    CombinationalClock(&'static str),
+    /// a wired contention policy is given for a tristated group whose pins are not of bool,
+    /// integer, bit vector or logic types, which cannot be combined bitwise
+    NotCombinable {
+        pin: (ChipPin, PinMetadata),
+        policy: ContentionPolicy,
+    },
+}
+
+// formats the pins of a group in a single line, such as `c1::p1 (Output u8), c2::p2 (Input u8)`
+fn format_pins(pins: &[(ChipPin, PinMetadata)]) -> String {
+    pins.iter()
+        .map(|(p, md)| format!("{}::{} ({} {})", p.chip, p.pin, md.pin_type, md.data_type))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::NoChips => write!(f, "cannot make pcb with no chips!"),
+            BuildError::SelfConnection(pin) => write!(
+                f,
+                "attempted to connect a pin to itself : chip `{}` pin `{}` appears to have a self-connection, which is redundant",
+                pin.chip, pin.pin
+            ),
+            BuildError::ExposedMultipleTimes { pin, as_names } => write!(
+                f,
+                "pin exposed multiple times : chip {} pin {} is exposed as {} and {}",
+                pin.chip, pin.pin, as_names.0, as_names.1
+            ),
+            BuildError::ExposedNoPins(as_name) => {
+                write!(f, "no pins given to expose as {}", as_name)
+            }
+            BuildError::ExposedNameReused(as_name) => write!(
+                f,
+                "multiple pins exposed as {} : pins to be exposed as the same pin must be given together, which shorts them",
+                as_name
+            ),
+            BuildError::ExposedShortConnected { as_name, pins } => write!(
+                f,
+                "exposed shorted pins {:?} (exposed as {}) are also shorted with non-exposed pins which is not allowed",
+                pins, as_name
+            ),
+            BuildError::MissingChip(chip) => {
+                write!(f, "chip {} defined in pcb design, but not added", chip)
+            }
+            BuildError::UndeclaredChip(chip) => {
+                write!(f, "chip {} added, but not defined in pcb design", chip)
+            }
+            BuildError::ChipTypeMismatch { chip, expected } => write!(
+                f,
+                "chip {} is declared to be of type {} in pcb design, but a chip of different type was added",
+                chip, expected
+            ),
+            BuildError::UnknownPin(pin) => write!(
+                f,
+                "Invalid chip added : chip {} expected to have pin named {}, not found",
+                pin.chip, pin.pin
+            ),
+            BuildError::IncompatiblePins { first, second } => write!(
+                f,
+                "Invalid chip connection : cannot connect {} to {}",
+                format_pins(std::slice::from_ref(first)),
+                format_pins(std::slice::from_ref(second))
+            ),
+            BuildError::MultipleDrivers(pins) => write!(
+                f,
+                "multiple output pins found in a non-tristated pin group : {}. Only groups where all pins are tristatable are allowed to have multiple output pins",
+                format_pins(pins)
+            ),
+            BuildError::MixedTristate(pins) => write!(
+                f,
+                "these pins are shorted, but not all are tristatable : {}. If any pin a a shorted pin group is tristatable, then all must be tristatable",
+                format_pins(pins)
+            ),
+            BuildError::ExposedShortNotInput { as_name, pin } => write!(
+                f,
+                "chip {} pin {} is expected to be input type, as it is exposed as {} and shorted with other pins, but was not. only input type pins are allowed to be shorted when exposing",
+                pin.0.chip, pin.0.pin, as_name
+            ),
+            BuildError::ExposedShortTypeMismatch {
+                as_name,
+                pin,
+                expected,
+            } => write!(
+                f,
+                "chip {} pin {} is expected to be of {} type, as it is exposed as {} and shorted with pin of that type, but was found to be of {} type",
+                pin.0.chip, pin.0.pin, expected.1.data_type, as_name, pin.1.data_type
+            ),
+            BuildError::NotTristated(pin) => write!(
+                f,
+                "contention policy or pull given for chip {} pin {}, but it is not in a tristated pin group. Only tristated groups can have multiple or no active pins",
+                pin.chip, pin.pin
+            ),
+            BuildError::PullTypeMismatch { pin, data_type } => write!(
+                f,
+                "pull value of type {} cannot be given to chip {} pin {} of type {}",
+                data_type, pin.0.chip, pin.0.pin, pin.1.data_type
+            ),
+            BuildError::CombinationalClock(chip) => write!(
+                f,
+                "combinational chip {} is given a clock other than the main clock, but combinational chips are settled on every tick of the pcb, so they can only be of the main clock",
+                chip
+            ),
+            BuildError::NotCombinable { pin, policy } => write!(
+                f,
+                "contention policy {:?} given for the tristated group of chip {} pin {}, but its values of type {} cannot be combined. Only bool, integer, bit vector and logic values can be",
+                policy, pin.0.chip, pin.0.pin, pin.1.data_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[derive(Debug)]
+/// This is the error given by Chip::try_tick when a simulation step fails. The paths are the names
+/// of chips from the outermost pcb separated by /, same as in StateError, such as `inner/c1/pin1`
+pub enum SimError {
+    /// a pin did not give its value, which can happen for manually implemented chips
+    /// which do not give values for all of their pins
+    MissingValue(String),
+    /// the value could not be set to the pin, such as when it is not of the data type of the pin
+    InvalidValue { path: String, error: PinValueError },
+    /// multiple pins of a tristated group with the Panic or Error contention policy were active at
+    /// the same time. The path is of the pcb which has the group, and is empty for the outermost pcb
+    Contention {
+        path: String,
+        contention: Contention,
+    },
+    /// multiple pins of a tristated group with a wired contention policy were active at the same
+    /// time, but gave values which cannot be combined, such as custom values of generic chips
+    NotCombinable {
+        path: String,
+        contention: Contention,
+    },
+    /// the combinational chips in a combinational loop which did not settle
+    Unsettled(Vec<String>),
+    /// the error given by the try_tick of a chip itself, along with the path of the chip
+    Chip {
+        path: String,
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl SimError {
+    /// gives the error for the given error of a chip, meant to be used in the try_tick of chips
+    pub fn chip(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        SimError::Chip {
+            path: String::new(),
+            error: error.into(),
+        }
+    }
+
+    /// prefixes the paths of the error with given chip name, used by pcbs for errors of their chips
+    pub fn in_chip(self, chip: &str) -> Self {
+        let prefix = |path: String| {
+            if path.is_empty() {
+                chip.to_string()
+            } else {
+                format!("{}/{}", chip, path)
+            }
+        };
+        match self {
+            SimError::MissingValue(path) => SimError::MissingValue(prefix(path)),
+            SimError::InvalidValue { path, error } => SimError::InvalidValue {
+                path: prefix(path),
+                error,
+            },
+            SimError::Contention { path, contention } => SimError::Contention {
+                path: prefix(path),
+                contention,
+            },
+            SimError::NotCombinable { path, contention } => SimError::NotCombinable {
+                path: prefix(path),
+                contention,
+            },
+            SimError::Unsettled(chips) => {
+                SimError::Unsettled(chips.into_iter().map(prefix).collect())
+            }
+            SimError::Chip { path, error } => SimError::Chip {
+                path: prefix(path),
+                error,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimError::MissingValue(path) => write!(f, "pin {} did not give its value", path),
+            SimError::InvalidValue { path, error } => {
+                write!(f, "cannot set value of pin {} : {}", path, error)
+            }
+            SimError::Contention { path, contention } if path.is_empty() => {
+                write!(f, "{}", contention)
+            }
+            SimError::Contention { path, contention } => write!(f, "in {} : {}", path, contention),
+            SimError::NotCombinable { path, contention } => write!(
+                f,
+                "values of pins {} in {} cannot be combined with the {:?} contention policy, only bool, integer, bit vector and logic values can be",
+                contention
+                    .drivers
+                    .iter()
+                    .map(|p| format!("{}::{}", p.chip, p.pin))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if path.is_empty() { "pcb" } else { path.as_str() },
+                contention.policy
+            ),
+            SimError::Unsettled(chips) => write!(
+                f,
+                "Combinational chips did not settle : the chips {:?} contain a combinational loop which oscillates",
+                chips
+            ),
+            SimError::Chip { path, error } => write!(f, "chip {} failed : {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
@@ -0,0 +1,155 @@
+use super::*;
+
+/// This is how a pcb accesses a pin of a chip : by the index given by ChipInterface::get_pin_index,
+/// or by its name for chips which do not support accessing pins by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinIndex {
+    Index(usize),
+    Name(&'static str),
+}
+
+/// This is a ChipPin resolved when building the pcb, to the position of the chip in the
+/// pcb and the index of the pin in the chip, so the pcb does not need to look up chips and pins
+/// by their names when ticking. The ChipPin is kept for showing in error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinSlot {
+    pub chip: usize,
+    pub pin: PinIndex,
+    pub name: ChipPin,
+}
+
+#[derive(Debug)]
+/// This is the ConnectedPins with its pins resolved to PinSlots. The tristated groups also
+/// have the policy for contention and the pull, which are set using set_contention_policies and set_pulls
+pub enum ResolvedPins {
+    Pair {
+        source: PinSlot,
+        destination: PinSlot,
+    },
+    Broadcast {
+        source: PinSlot,
+        destinations: Vec<PinSlot>,
+    },
+    Tristated {
+        sources: Vec<PinSlot>,
+        destinations: Vec<PinSlot>,
+        policy: ContentionPolicy,
+        pull: Option<Pull>,
+        // the last value driven on the bus, which is only kept for groups with a bus keeper
+        kept: Option<PinValue>,
+    },
+}
+
+impl PinIndex {
+    pub fn get_value(self, chip: &dyn HardwareModule) -> Option<PinValue> {
+        match self {
+            PinIndex::Index(i) => chip.get_pin_value_at(i),
+            PinIndex::Name(n) => chip.get_pin_value(n),
+        }
+    }
+
+    pub fn set_value(
+        self,
+        chip: &mut dyn HardwareModule,
+        val: &PinValue,
+    ) -> Result<(), PinValueError> {
+        match self {
+            PinIndex::Index(i) => chip.set_pin_value_at(i, val),
+            PinIndex::Name(n) => chip.set_pin_value(n, val),
+        }
+    }
+
+    pub fn is_tristated(self, chip: &dyn HardwareModule) -> bool {
+        match self {
+            PinIndex::Index(i) => chip.is_pin_tristated_at(i),
+            PinIndex::Name(n) => chip.is_pin_tristated(n),
+        }
+    }
+
+    pub fn in_input_mode(self, chip: &dyn HardwareModule) -> bool {
+        match self {
+            PinIndex::Index(i) => chip.in_input_mode_at(i),
+            PinIndex::Name(n) => chip.in_input_mode(n),
+        }
+    }
+
+    pub fn value_eq(self, chip: &dyn HardwareModule, val: &PinValue) -> bool {
+        match self {
+            PinIndex::Index(i) => chip.pin_value_eq_at(i, val),
+            PinIndex::Name(n) => chip.pin_value_eq(n, val),
+        }
+    }
+}
+
+/// gives the index of the pin in the chip, or its name if the chip does not support indices
+pub fn get_pin_index(chip: &dyn HardwareModule, pin: &'static str) -> PinIndex {
+    match chip.get_pin_index(pin) {
+        Some(i) => PinIndex::Index(i),
+        None => PinIndex::Name(pin),
+    }
+}
+
+/// resolves the pin to its PinSlot, the chip names are in the same order as the chips.
+/// This assumes the chip is present in the pcb, which is validated when building
+pub fn resolve_pin(names: &[String], chips: &[Box<PcbChip>], pin: ChipPin) -> PinSlot {
+    let chip = names.iter().position(|n| n == pin.chip).unwrap();
+    PinSlot {
+        chip,
+        pin: get_pin_index(chips[chip].as_ref(), pin.pin),
+        name: pin,
+    }
+}
+
+/// resolves the pins of each of the connections to their PinSlots
+pub fn resolve_connections(
+    names: &[String],
+    chips: &[Box<PcbChip>],
+    connections: &[ConnectedPins],
+) -> Vec<ResolvedPins> {
+    let resolve = |pin: &ChipPin| resolve_pin(names, chips, *pin);
+    connections
+        .iter()
+        .map(|c| match c {
+            ConnectedPins::Pair {
+                source,
+                destination,
+            } => ResolvedPins::Pair {
+                source: resolve(source),
+                destination: resolve(destination),
+            },
+            ConnectedPins::Broadcast {
+                source,
+                destinations,
+            } => ResolvedPins::Broadcast {
+                source: resolve(source),
+                destinations: destinations.iter().map(resolve).collect(),
+            },
+            ConnectedPins::Tristated {
+                sources,
+                destinations,
+            } => ResolvedPins::Tristated {
+                sources: sources.iter().map(resolve).collect(),
+                destinations: destinations.iter().map(resolve).collect(),
+                policy: ContentionPolicy::default(),
+                pull: None,
+                kept: None,
+            },
+        })
+        .collect()
+}
+
+/// takes out the chips from the map in the given order, along with their names. The chips left in the
+/// map, which were added under names not declared in the pcb, are given as errors
+pub fn into_chip_slots(
+    mut chips: HashMap<String, Box<PcbChip>>,
+    order: &[String],
+    errors: &mut Vec<BuildError>,
+) -> (Vec<String>, Vec<Box<PcbChip>>) {
+    let names = order.to_vec();
+    let slots = order.iter().map(|n| chips.remove(n).unwrap()).collect();
+    // sorted so the errors are in the same order on each build
+    let mut undeclared: Vec<_> = chips.into_keys().collect();
+    undeclared.sort();
+    errors.extend(undeclared.into_iter().map(BuildError::UndeclaredChip));
+    (names, slots)
+}
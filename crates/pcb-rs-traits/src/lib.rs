@@ -1,9 +1,50 @@
 use downcast_rs::{impl_downcast, Downcast}; // TODO someday remove this dependency and implement the feature in this crate itself
-use std::any::Any;
+use std::any::TypeId;
 use std::collections::HashMap;
 
+mod clock;
+mod contention;
+mod descriptor;
+mod dot;
+mod dynamic;
+mod error;
+mod index;
+mod logic;
+#[cfg(feature = "netlist")]
+mod netlist;
+mod pull;
+mod state;
 mod util;
-pub use util::get_pin_group;
+mod validate;
+mod value;
+mod vcd;
+pub use clock::{edge_cycle, restore_cycle, save_cycle, Clock, ClockEdge};
+pub use contention::{contention_error, set_contention_policies, Contention, ContentionPolicy};
+pub use descriptor::{exposed_pin, find_pin, ChipPins, NoChipPins, PinDescriptor, PinsOf};
+pub use dynamic::{DynamicPcb, DynamicPcbBuilder};
+pub use error::{BuildError, SimError};
+pub use index::{
+    get_pin_index, into_chip_slots, resolve_connections, resolve_pin, PinIndex, PinSlot,
+    ResolvedPins,
+};
+pub use logic::{Logic, LogicVec};
+#[cfg(feature = "netlist")]
+pub use netlist::{ChipRegistry, Netlist, NetlistError};
+pub use pull::{clear_keepers, set_pulls, Pull};
+pub use state::{restore_chips, restore_pins, save_chips, save_pins, ChipState, StateError};
+pub use util::{
+    get_combinational_chips, get_pin_group, propagate_pin_values, settle_combinational_chips,
+    tick_chips, ScratchBuffers,
+};
+pub use validate::{
+    check_added_chips, check_exposed_pins, check_pin_connections, check_valid_chips,
+    get_connected_pins, get_pin_metadata, get_short_pin_set, used_pins,
+};
+pub use value::{
+    generic_from_pin_value, generic_to_pin_value, CustomPinData, CustomValue, PinData, PinValue,
+    PinValueError, ValueKind,
+};
+pub use vcd::{VcdError, VcdTracer, VcdValue};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PinType {
@@ -13,13 +54,14 @@ pub enum PinType {
 }
 #[derive(Debug, Clone, Copy)]
 /// This will store the metadata of the pin, for the encompassing
-/// module (usually generated using pcb!) to use. Reason that the data_type is
-/// &'static str is that when deriving the Chip using Chip derive macro,
-/// or even when hand-implementing ChipInterface, the data type of the
-/// pin will be known in advance. Name is not stored here as it will be the key of hashmap
+/// module (usually generated using pcb!) to use. The data_type is the
+/// std::any::type_name of the pin type, which is only meant for showing to users, and
+/// type_id is the std::any::TypeId of it, which is used to check if two pins are of same type.
+/// Name is not stored here as it will be the key of hashmap
 pub struct PinMetadata {
     pub pin_type: PinType,
     pub data_type: &'static str,
+    pub type_id: TypeId,
     pub tristatable: bool,
 }
 
@@ -32,6 +74,15 @@ pub struct ChipPin {
     pub pin: &'static str,
 }
 
+/// This marks a chip declared with a type in pcb!, such as `chip c1: Chip1;`, which is not yet
+/// added to the generated builder. The builder can only be built once all such chips are added
+#[derive(Debug, Clone, Copy)]
+pub struct ChipMissing;
+
+/// This marks a chip declared with a type in pcb!, which is added to the generated builder
+#[derive(Debug, Clone, Copy)]
+pub struct ChipAdded;
+
 #[derive(Debug)]
 // NOTE: if you don't understand rest of this comment it is fine, even I'm a bit confused after writing it!
 // Note that in terms of the chip, a pin will be `output`, i.e. it will give out some data after tick()
@@ -68,13 +119,13 @@ impl PinMetadata {
         let both_output =
             matches!(self.pin_type, PinType::Output) && matches!(other.pin_type, PinType::Output);
         let both_tristatable = self.tristatable ^ other.tristatable;
-        let both_same_type = self.data_type == other.data_type;
+        let both_same_type = self.type_id == other.type_id;
 
         // for pins to be connectable, both should NOT be input, both should NOT be output
         // and both either should or should not be tristatable, the xor gives true if one is and one isn't
         // so we check for it being false as well, and both should be of same type
 
-        return !both_input && !both_output && !both_tristatable && both_same_type;
+        !both_input && !both_output && !both_tristatable && both_same_type
     }
 }
 
@@ -92,14 +143,6 @@ impl std::fmt::Display for PinType {
     }
 }
 
-// Ideally we should enforce that the value type should be Any+Clone,
-// as we will call clone in the set_pin_value,
-// but adding clone to is not allowed due to object safety
-// so instead we only use Any, and depend on the fact that
-// before using clone, we will be converting the trait object back to concrete
-// so clone can be called, and if the type itself does not implement clone,
-// that call will fail at compile time
-
 /// This is the interface which should be exposed by the chip struct,
 /// and will be used by the pcb module. This is meant to be implemented
 /// by the #[Derive(Chip)] macro, but can also be manually implemented if needed
@@ -107,15 +150,16 @@ pub trait ChipInterface {
     /// gives a mapping from pin name to pin metadata
     fn get_pin_list(&self) -> HashMap<&'static str, PinMetadata>;
 
-    /// returns value of a specific pin, typecasted to Any
-    fn get_pin_value(&self, name: &str) -> Option<Box<dyn Any>>;
+    /// returns value of a specific pin
+    fn get_pin_value(&self, name: &str) -> Option<PinValue>;
 
-    /// sets value of a specific pin, from the given reference
-    fn set_pin_value(&mut self, name: &str, val: &dyn Any);
+    /// sets value of a specific pin, from the given reference. Gives an error if
+    /// there is no such pin, or if the value is not of the data type of the pin
+    fn set_pin_value(&mut self, name: &str, val: &PinValue) -> Result<(), PinValueError>;
 
     // The reason to include it in Chip interface, rather than anywhere else,
     // is that I couldn't find a more elegant solution that can either directly
-    // implement on pin values which are typecasted to dyn Any. Thus the only way
+    // implement on pin values which are converted to PinValue. Thus the only way
     // that we can absolutely make sure if a pin is tristated or not is in the
     // Chip-level rather than the pin level. One major issue is that the data of
     // which type the pin is is only available in the Chip derive macro, and cannot be
@@ -126,6 +170,94 @@ pub trait ChipInterface {
 
     /// This returns if the io pin is in input mode or not, and false for other pins
     fn in_input_mode(&self, name: &str) -> bool;
+
+    /// This returns true if the outputs of the chip depend only on its current inputs,
+    /// i.e. it has no internal state. Such chips are re-ticked by the encompassing
+    /// pcb until the connected pin values settle, so there is no clock-cycle delay
+    /// across them. Set by #[combinational] on a Chip derived struct
+    fn is_combinational(&self) -> bool {
+        false
+    }
+
+    /// This returns if the value of given output or io pin is equal to the given value.
+    /// It is used by the encompassing pcb to check if a combinational chip has settled, so
+    /// combinational chips must implement this, otherwise they are always considered to be changing
+    fn pin_value_eq(&self, _name: &str, _val: &PinValue) -> bool {
+        false
+    }
+
+    /// This gives the index of the pin with given name, which can be used with the *_at functions to
+    /// access the pin without comparing its name. The pcbs resolve the pins to indices when building,
+    /// so they do not have to look up pins by name when ticking. This is implemented by the Chip derive,
+    /// chips which do not implement this give None, which is the default, and are accessed by pin names
+    fn get_pin_index(&self, _name: &str) -> Option<usize> {
+        None
+    }
+
+    /// same as get_pin_value, for the pin with given index
+    fn get_pin_value_at(&self, _index: usize) -> Option<PinValue> {
+        None
+    }
+
+    /// same as set_pin_value, for the pin with given index
+    fn set_pin_value_at(&mut self, index: usize, _val: &PinValue) -> Result<(), PinValueError> {
+        Err(PinValueError::UnknownPin(index.to_string()))
+    }
+
+    /// same as is_pin_tristated, for the pin with given index
+    fn is_pin_tristated_at(&self, _index: usize) -> bool {
+        false
+    }
+
+    /// same as in_input_mode, for the pin with given index
+    fn in_input_mode_at(&self, _index: usize) -> bool {
+        false
+    }
+
+    /// same as pin_value_eq, for the pin with given index
+    fn pin_value_eq_at(&self, _index: usize, _val: &PinValue) -> bool {
+        false
+    }
+
+    /// This resets the chip to its initial state. It is implemented by the Chip derive when the
+    /// struct has #[reset], and by pcbs, where it resets all of the component chips. Chips which
+    /// do not implement this keep their state as it is, which is the default
+    fn reset(&mut self) {}
+
+    /// This saves the state of the chip, so it can be restored later using restore_state.
+    /// By default this saves the values of all pins, the Chip derive also saves the fields
+    /// marked #[state], and pcbs save the states of all their chips
+    fn save_state(&self) -> ChipState {
+        state::save_pins(self)
+    }
+
+    /// This restores the state saved by save_state, which can be from another chip of the same type.
+    /// Gives an error if any saved value cannot be set, in which case the state is partially restored
+    fn restore_state(&mut self, state: &ChipState) -> Result<(), StateError> {
+        state::restore_pins(self, state)
+    }
+
+    /// This gives the component chips of this chip along with their names, and is
+    /// used to walk through nested pcbs. Chips which are not made up of other chips
+    /// do not have any, which is the default
+    fn get_chips(&self) -> Vec<(&str, &dyn HardwareModule)> {
+        Vec::new()
+    }
+
+    /// This gives the connections between the component chips of this chip, where the
+    /// ChipPins refer to the chips given by get_chips. Chips which are not made up of
+    /// other chips do not have any, which is the default
+    fn get_pin_connections(&self) -> &[ConnectedPins] {
+        &[]
+    }
+
+    /// This gives a Graphviz DOT graph of the component chips and their connections,
+    /// with nested pcbs shown as clusters. The pin ports are coloured by their type,
+    /// input as blue, output as red and io as green, and tristated groups of pins
+    /// are shown connected through a bus node
+    fn to_dot(&self) -> String {
+        dot::to_dot(self)
+    }
 }
 
 /// This is intended to be implemented manually by user
@@ -146,7 +278,36 @@ pub trait Chip {
     /// Thus ideally this function should check values of its input pins, take according actions and
     /// set values of output pins. Although in case the chip itself needs to do something else, (eg logging etc)
     /// it can simply do that and not set any pin to output in its struct declaration.
-    fn tick(&mut self) -> ();
+    ///
+    /// Chips which can fail give an error, which the pcbs give along with the path of the chip,
+    /// so the errors can be reported and the simulation continued.
+    fn try_tick(&mut self) -> Result<(), SimError>;
+
+    /// This is the infallible version of try_tick, which panics if it gives an error.
+    fn tick(&mut self) {
+        if let Err(e) = self.try_tick() {
+            panic!("{}", e);
+        }
+    }
+
+    /// This is called by the pcbs on each edge of the clock, instead of try_tick. Chips which
+    /// sample on a specific edge, or do different things on both edges such as master-slave flip-flops,
+    /// can implement this. By default this calls try_tick on the rising edge and does nothing on the
+    /// falling edge, so chips which do not implement this are ticked once per clock cycle as usual.
+    ///
+    /// The pcbs only drive the falling edge when any of their chips gives true from uses_falling_edge,
+    /// so chips which do anything on the falling edge must implement that as well
+    fn try_tick_edge(&mut self, edge: ClockEdge) -> Result<(), SimError> {
+        match edge {
+            ClockEdge::Rising => self.try_tick(),
+            ClockEdge::Falling => Ok(()),
+        }
+    }
+
+    /// tells if the chip does anything on the falling edge of the clock, false by default
+    fn uses_falling_edge(&self) -> bool {
+        false
+    }
 }
 
 /// This trait is used to create trait objects to store in the pcb created by the pbc! macro
@@ -159,3 +320,45 @@ pub trait HardwareModule: ChipInterface + Chip + Downcast {}
 impl_downcast!(HardwareModule);
 
 impl<T> HardwareModule for T where T: ChipInterface + Chip + Downcast {}
+
+/// This is the Send variant of HardwareModule, which is auto implemented for all the chips which are Send
+pub trait SendHardwareModule: HardwareModule + Send {}
+
+impl<T> SendHardwareModule for T where T: HardwareModule + Send {}
+
+/// This is how the pcbs store their chips. With the `parallel` feature the pcbs tick their chips on
+/// multiple threads, so they store them as SendHardwareModule, and only chips which are Send can be added
+/// to pcbs. Chips which are not used in pcbs do not need to be Send, as HardwareModule stays the same
+#[cfg(not(feature = "parallel"))]
+pub type PcbChip = dyn HardwareModule;
+
+/// This is how the pcbs store their chips, see the other definition
+#[cfg(feature = "parallel")]
+pub type PcbChip = dyn SendHardwareModule;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin<T: 'static>(pin_type: PinType, tristatable: bool) -> PinMetadata {
+        PinMetadata {
+            pin_type,
+            data_type: std::any::type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            tristatable,
+        }
+    }
+
+    #[test]
+    fn pins_of_same_type_id_are_connectable() {
+        let out = pin::<u8>(PinType::Output, false);
+        assert!(out.is_connectable(&pin::<u8>(PinType::Input, false)));
+        assert!(out.is_connectable(&pin::<u8>(PinType::IO, false)));
+        assert!(!out.is_connectable(&pin::<i8>(PinType::Input, false)));
+        assert!(!out.is_connectable(&pin::<u8>(PinType::Output, false)));
+        assert!(!pin::<u8>(PinType::Input, false).is_connectable(&pin::<u8>(PinType::Input, false)));
+        let tristated = pin::<Option<u8>>(PinType::Output, true);
+        assert!(tristated.is_connectable(&pin::<std::option::Option<u8>>(PinType::Input, true)));
+        assert!(!tristated.is_connectable(&pin::<Option<u8>>(PinType::Input, false)));
+    }
+}
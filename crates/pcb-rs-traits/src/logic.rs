@@ -0,0 +1,438 @@
+use super::*;
+use std::ops::{BitAnd, BitOr, BitXor, Index, IndexMut, Not};
+
+/// This is a four valued logic level, for gate level simulations. Along with 0 and 1, a pin
+/// can be X, i.e. unknown, as for uninitialized values or pins driven to different levels by
+/// multiple chips, and Z, i.e. high impedance, as for pins which are not driven at all.
+/// Pins of this type are tristatable, where Z is the tristated state, and when multiple chips
+/// drive a bus of these pins at the same time, the pcb resolves the levels using Logic::resolve
+/// instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Logic {
+    Zero,
+    One,
+    /// unknown level, this is the default, as the pins are uninitialized
+    #[default]
+    X,
+    /// high impedance, i.e. the pin is not driven
+    Z,
+}
+
+impl Logic {
+    /// gives the level of a wire driven by both the given levels : Z is
+    /// overridden by the other level, and two different levels give X
+    pub fn resolve(self, other: Logic) -> Logic {
+        match (self, other) {
+            (Logic::Z, l) | (l, Logic::Z) => l,
+            (a, b) if a == b => a,
+            _ => Logic::X,
+        }
+    }
+
+    pub fn is_z(self) -> bool {
+        self == Logic::Z
+    }
+
+    pub fn is_x(self) -> bool {
+        self == Logic::X
+    }
+
+    /// gives if the level is 0 or 1
+    pub fn is_known(self) -> bool {
+        matches!(self, Logic::Zero | Logic::One)
+    }
+
+    /// gives the level as bool, if it is 0 or 1
+    pub fn to_bool(self) -> Option<bool> {
+        match self {
+            Logic::Zero => Some(false),
+            Logic::One => Some(true),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Logic::Zero => '0',
+            Logic::One => '1',
+            Logic::X => 'x',
+            Logic::Z => 'z',
+        }
+    }
+}
+
+impl From<bool> for Logic {
+    fn from(b: bool) -> Self {
+        if b {
+            Logic::One
+        } else {
+            Logic::Zero
+        }
+    }
+}
+
+impl std::fmt::Display for Logic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// the gate operations treat a Z input as X, as a gate reading a floating input
+// cannot know its level. A known input can still decide the output, such as 0 for and
+
+impl Not for Logic {
+    type Output = Logic;
+    fn not(self) -> Logic {
+        match self {
+            Logic::Zero => Logic::One,
+            Logic::One => Logic::Zero,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl BitAnd for Logic {
+    type Output = Logic;
+    fn bitand(self, rhs: Logic) -> Logic {
+        match (self, rhs) {
+            (Logic::Zero, _) | (_, Logic::Zero) => Logic::Zero,
+            (Logic::One, Logic::One) => Logic::One,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl BitOr for Logic {
+    type Output = Logic;
+    fn bitor(self, rhs: Logic) -> Logic {
+        match (self, rhs) {
+            (Logic::One, _) | (_, Logic::One) => Logic::One,
+            (Logic::Zero, Logic::Zero) => Logic::Zero,
+            _ => Logic::X,
+        }
+    }
+}
+
+impl BitXor for Logic {
+    type Output = Logic;
+    fn bitxor(self, rhs: Logic) -> Logic {
+        match (self.to_bool(), rhs.to_bool()) {
+            (Some(a), Some(b)) => Logic::from(a ^ b),
+            _ => Logic::X,
+        }
+    }
+}
+
+/// This is a vector of N Logic levels, such as a bus, where the first element is the least
+/// significant bit. As Logic, pins of this type are tristatable, where the vector is tristated
+/// when all the levels are Z, and the levels of multiple drivers are resolved bit by bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogicVec<const N: usize>(pub [Logic; N]);
+
+impl<const N: usize> Default for LogicVec<N> {
+    fn default() -> Self {
+        Self([Logic::X; N])
+    }
+}
+
+impl<const N: usize> LogicVec<N> {
+    /// gives the vector with all levels set to the given level
+    pub fn splat(level: Logic) -> Self {
+        Self([level; N])
+    }
+
+    /// gives the vector with the bits of the given value, bits beyond N are ignored
+    pub fn from_u128(val: u128) -> Self {
+        let mut ret = [Logic::Zero; N];
+        for (i, l) in ret.iter_mut().enumerate() {
+            *l = Logic::from(i < 128 && val & (1 << i) != 0);
+        }
+        Self(ret)
+    }
+
+    /// gives the value of the bits, if all the levels are 0 or 1
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.iter().enumerate().try_fold(0_u128, |acc, (i, l)| {
+            l.to_bool().map(|b| acc | ((b as u128) << i))
+        })
+    }
+
+    /// resolves the levels bit by bit, see Logic::resolve
+    pub fn resolve(self, other: Self) -> Self {
+        self.zip(other, Logic::resolve)
+    }
+
+    /// gives if all the levels are Z, i.e. the vector is not driven
+    pub fn is_z(&self) -> bool {
+        self.0.iter().all(|l| l.is_z())
+    }
+
+    /// gives if all the levels are 0 or 1
+    pub fn is_known(&self) -> bool {
+        self.0.iter().all(|l| l.is_known())
+    }
+
+    fn zip(self, other: Self, f: impl Fn(Logic, Logic) -> Logic) -> Self {
+        let mut ret = self.0;
+        for (l, r) in ret.iter_mut().zip(other.0) {
+            *l = f(*l, r);
+        }
+        Self(ret)
+    }
+}
+
+impl<const N: usize> Index<usize> for LogicVec<N> {
+    type Output = Logic;
+    fn index(&self, i: usize) -> &Logic {
+        &self.0[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for LogicVec<N> {
+    fn index_mut(&mut self, i: usize) -> &mut Logic {
+        &mut self.0[i]
+    }
+}
+
+impl<const N: usize> Not for LogicVec<N> {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(self.0.map(|l| !l))
+    }
+}
+
+impl<const N: usize> BitAnd for LogicVec<N> {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        self.zip(rhs, Logic::bitand)
+    }
+}
+
+impl<const N: usize> BitOr for LogicVec<N> {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        self.zip(rhs, Logic::bitor)
+    }
+}
+
+impl<const N: usize> BitXor for LogicVec<N> {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        self.zip(rhs, Logic::bitxor)
+    }
+}
+
+/// shows the levels with the most significant bit first, such as `10xz`
+impl<const N: usize> std::fmt::Display for LogicVec<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s: String = self.0.iter().rev().map(|l| l.to_char()).collect();
+        write!(f, "{}", s)
+    }
+}
+
+// serde only implements its traits for arrays of at most 32 elements, so the vectors cannot derive
+// them as Logic does, and the levels are written as a tuple of N levels here instead
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for LogicVec<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut levels = serializer.serialize_tuple(N)?;
+        for l in &self.0 {
+            levels.serialize_element(l)?;
+        }
+        levels.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for LogicVec<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Levels<const N: usize>;
+
+        impl<'de, const N: usize> serde::de::Visitor<'de> for Levels<N> {
+            type Value = LogicVec<N>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "a sequence of {} logic levels", N)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<LogicVec<N>, A::Error> {
+                let mut ret = [Logic::X; N];
+                for (i, l) in ret.iter_mut().enumerate() {
+                    *l = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                Ok(LogicVec(ret))
+            }
+        }
+
+        deserializer.deserialize_tuple(N, Levels::<N>)
+    }
+}
+
+// the levels are stored in PinValue::Logic as bit masks, where x and z have the bits which are
+// X or Z, and bits has the bits which are 1
+
+pub(crate) fn to_masks(levels: &[Logic]) -> (u128, u128, u128) {
+    let (mut bits, mut x, mut z) = (0, 0, 0);
+    for (i, l) in levels.iter().enumerate() {
+        match l {
+            Logic::Zero => {}
+            Logic::One => bits |= 1 << i,
+            Logic::X => x |= 1 << i,
+            Logic::Z => z |= 1 << i,
+        }
+    }
+    (bits, x, z)
+}
+
+pub(crate) fn level_at(bits: u128, x: u128, z: u128, i: u8) -> Logic {
+    if z & (1 << i) != 0 {
+        Logic::Z
+    } else if x & (1 << i) != 0 {
+        Logic::X
+    } else {
+        Logic::from(bits & (1 << i) != 0)
+    }
+}
+
+impl PinData for Logic {
+    const WIDTH: u8 = 1;
+
+    fn to_pin_value(&self) -> PinValue {
+        let (bits, x, z) = to_masks(&[*self]);
+        PinValue::Logic {
+            bits,
+            x,
+            z,
+            width: 1,
+        }
+    }
+
+    fn from_pin_value(val: &PinValue) -> Option<Self> {
+        match val {
+            PinValue::Logic { bits, x, z, width } if *width == 1 => {
+                Some(level_at(*bits, *x, *z, 0))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<const N: usize> PinData for LogicVec<N> {
+    // this fails to compile when the vector is used as a pin, if it is wider than
+    // what the Logic variant can hold
+    const WIDTH: u8 = {
+        assert!(N <= 128, "logic vector pins can be at most 128 bits wide");
+        N as u8
+    };
+
+    fn to_pin_value(&self) -> PinValue {
+        let (bits, x, z) = to_masks(&self.0);
+        PinValue::Logic {
+            bits,
+            x,
+            z,
+            width: Self::WIDTH,
+        }
+    }
+
+    fn from_pin_value(val: &PinValue) -> Option<Self> {
+        match val {
+            PinValue::Logic { bits, x, z, width } if *width == Self::WIDTH => {
+                let mut ret = [Logic::X; N];
+                for (i, l) in ret.iter_mut().enumerate() {
+                    *l = level_at(*bits, *x, *z, i as u8);
+                }
+                Some(Self(ret))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Logic::*;
+
+    const LEVELS: [Logic; 4] = [Zero, One, X, Z];
+
+    #[test]
+    fn z_is_overridden_and_different_levels_give_x() {
+        for l in LEVELS {
+            assert_eq!(Z.resolve(l), l);
+            assert_eq!(l.resolve(Z), l);
+            assert_eq!(l.resolve(l), l);
+        }
+        assert_eq!(Zero.resolve(One), X);
+        assert_eq!(One.resolve(X), X);
+    }
+
+    #[test]
+    fn known_inputs_decide_gate_outputs() {
+        for l in LEVELS {
+            assert_eq!(Zero & l, Zero);
+            assert_eq!(One | l, One);
+        }
+        assert_eq!(One & One, One);
+        assert_eq!(One & Z, X);
+        assert_eq!(Zero | X, X);
+        assert_eq!(One ^ Zero, One);
+        assert_eq!(One ^ Z, X);
+        assert_eq!(!Zero, One);
+        assert_eq!(!Z, X);
+    }
+
+    #[test]
+    fn vectors_convert_to_and_from_integers() {
+        let v = LogicVec::<4>::from_u128(0b1010);
+        assert_eq!(v.0, [Zero, One, Zero, One]);
+        assert_eq!(v.to_u128(), Some(0b1010));
+        // bits beyond the width are ignored
+        assert_eq!(LogicVec::<2>::from_u128(0b111).to_u128(), Some(0b11));
+        let mut v = v;
+        v[1] = X;
+        assert_eq!(v.to_u128(), None);
+        assert!(!v.is_known());
+        assert_eq!(v.to_string(), "10x0");
+        assert_eq!(LogicVec::<3>::default().to_string(), "xxx");
+        assert!(LogicVec::<3>::splat(Z).is_z());
+    }
+
+    #[test]
+    fn vectors_are_resolved_bit_by_bit() {
+        let a = LogicVec([Zero, Z, One, Z]);
+        let b = LogicVec([Z, One, Zero, Z]);
+        assert_eq!(a.resolve(b).0, [Zero, One, X, Z]);
+        assert_eq!((a & b).0, [Zero, X, Zero, X]);
+        assert_eq!((!a).0, [One, X, Zero, X]);
+    }
+
+    #[test]
+    fn levels_roundtrip_through_pin_values() {
+        for l in LEVELS {
+            assert_eq!(l.to_pin_value().get::<Logic>(), Some(l));
+        }
+        let v = LogicVec([Zero, One, X, Z, One]);
+        assert_eq!(v.to_pin_value().get::<LogicVec<5>>(), Some(v));
+        assert_eq!(v.to_pin_value().get::<LogicVec<4>>(), None);
+        assert_eq!(v.to_pin_value().get::<Logic>(), None);
+    }
+
+    // serde_json is only a dependency with the netlist feature
+    #[cfg(feature = "netlist")]
+    #[test]
+    fn vectors_wider_than_serde_arrays_are_serialized() {
+        let v = LogicVec::<40>::from_u128(0b1011);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(serde_json::from_str::<LogicVec<40>>(&json).unwrap(), v);
+        assert!(serde_json::from_str::<LogicVec<41>>(&json).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use super::*;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// This is the description of a pcb, as loaded from a json or toml file. It mirrors the
+/// pcb! syntax, where chips maps the chip names to their type keys in the ChipRegistry, and are
+/// kept in the order of the file, as that is the order in which they are added and ticked,
+/// connections are written as `c1::p1 - c2::p2` and exposed pins as `c1::p1,c2::p2 as p3`, where the
+/// pins can also be buses such as `cpu::addr[0..8] - ram::a[0..8]`.
+/// For example in toml :
+///
+/// ```toml
+/// connections = ["cpu::data_bus - ram::data"]
+/// expose = ["cpu::reset as reset"]
+///
+/// [chips]
+/// cpu = "cpu"
+/// ram = "ram_64k"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Netlist {
+    #[serde(deserialize_with = "chips_in_order")]
+    pub chips: Vec<(String, String)>,
+    #[serde(default)]
+    pub connections: Vec<String>,
+    #[serde(default)]
+    pub expose: Vec<String>,
+}
+
+// the chips are a map in the files, but maps do not keep the order of their keys, so the
+// entries are collected in the order the deserializer gives them, which is the file order
+fn chips_in_order<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(String, String)>, D::Error> {
+    struct ChipsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for ChipsVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "a map of chip names to their types")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut chips = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                chips.push(entry);
+            }
+            Ok(chips)
+        }
+    }
+
+    d.deserialize_map(ChipsVisitor)
+}
+
+/// This maps the type keys used in netlist files to the functions which construct the chips
+#[derive(Default)]
+pub struct ChipRegistry {
+    constructors: HashMap<String, Box<dyn Fn() -> Box<PcbChip>>>,
+}
+
+#[derive(Debug)]
+/// This is the error given when loading a pcb from a netlist fails
+pub enum NetlistError {
+    /// the file is not valid json / toml, or does not have the netlist structure
+    Parse(String),
+    /// a connection or expose entry is not of the expected syntax
+    Syntax(String),
+    /// the pins on both sides of a connection or expose entry are buses of different widths
+    BusWidthMismatch(String),
+    /// the type key of a chip is not registered in the ChipRegistry
+    UnknownChipType { chip: String, chip_type: String },
+    /// the pcb described by the netlist is not valid
+    Build(Vec<BuildError>),
+}
+
+impl std::fmt::Display for NetlistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetlistError::Parse(e) => write!(f, "invalid netlist : {}", e),
+            NetlistError::Syntax(entry) => write!(
+                f,
+                "invalid netlist entry `{}` : expected `chip::pin - chip::pin` for connections and `chip::pin,chip::pin as name` for exposed pins",
+                entry
+            ),
+            NetlistError::BusWidthMismatch(entry) => write!(
+                f,
+                "invalid netlist entry `{}` : the buses have different number of pins",
+                entry
+            ),
+            NetlistError::UnknownChipType { chip, chip_type } => write!(
+                f,
+                "chip {} is of type {}, which is not registered",
+                chip, chip_type
+            ),
+            NetlistError::Build(errors) => {
+                write!(f, "invalid pcb in netlist : ")?;
+                let errors: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetlistError {}
+
+impl ChipRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// registers the function used to construct chips of the given type key
+    pub fn register<F>(mut self, chip_type: &str, constructor: F) -> Self
+    where
+        F: Fn() -> Box<PcbChip> + 'static,
+    {
+        self.constructors
+            .insert(chip_type.to_string(), Box::new(constructor));
+        self
+    }
+
+    /// registers the given type key to construct chips using their Default impl
+    #[cfg(not(feature = "parallel"))]
+    pub fn register_default<T: HardwareModule + Default>(self, chip_type: &str) -> Self {
+        self.register(chip_type, || Box::new(T::default()))
+    }
+
+    /// registers the given type key to construct chips using their Default impl. With the
+    /// `parallel` feature the chips of pcbs must be Send, see PcbChip
+    #[cfg(feature = "parallel")]
+    pub fn register_default<T: SendHardwareModule + Default>(self, chip_type: &str) -> Self {
+        self.register(chip_type, || Box::new(T::default()))
+    }
+
+    fn construct(&self, chip_type: &str) -> Option<Box<PcbChip>> {
+        self.constructors.get(chip_type).map(|c| c())
+    }
+}
+
+// the names in ChipPin are 'static, so we leak the names read from the netlists. The names are
+// interned, so each name is only leaked once, however many times netlists using it are loaded
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match names.get(name) {
+        Some(n) => n,
+        None => {
+            let n: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.insert(n);
+            n
+        }
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// parses `name`, `name[i]` or `name[start..end]`, giving the names of the pins of the bus
+// as in pcb!, i.e. `name0, name1 ...`
+fn parse_pin_names(s: &str) -> Option<Vec<String>> {
+    let s = s.trim();
+    let (name, range) = match s.split_once('[') {
+        None => return is_ident(s).then(|| vec![s.to_string()]),
+        Some((name, range)) => (name.trim(), range.strip_suffix(']')?),
+    };
+    if !is_ident(name) {
+        return None;
+    }
+    let (start, end) = match range.split_once("..") {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let i: usize = range.trim().parse().ok()?;
+            (i, i + 1)
+        }
+    };
+    if start >= end {
+        return None;
+    }
+    Some((start..end).map(|i| format!("{}{}", name, i)).collect())
+}
+
+fn parse_chip_pins(s: &str) -> Option<Vec<ChipPin>> {
+    let (chip, pin) = s.trim().split_once("::")?;
+    let chip = chip.trim();
+    if !is_ident(chip) {
+        return None;
+    }
+    let chip = intern(chip);
+    Some(
+        parse_pin_names(pin)?
+            .iter()
+            .map(|pin| ChipPin {
+                chip,
+                pin: intern(pin),
+            })
+            .collect(),
+    )
+}
+
+// parses `c1::p1 - c2::p2`, the trailing ; of pcb! syntax is allowed.
+// The pins can be buses, so this gives each pair of the connected pins
+fn parse_connection(entry: &str) -> Result<Vec<(ChipPin, ChipPin)>, NetlistError> {
+    let syntax_err = || NetlistError::Syntax(entry.to_string());
+    let (pins1, pins2) = entry
+        .trim()
+        .trim_end_matches(';')
+        .split_once('-')
+        .ok_or_else(syntax_err)?;
+    let pins1 = parse_chip_pins(pins1).ok_or_else(syntax_err)?;
+    let pins2 = parse_chip_pins(pins2).ok_or_else(syntax_err)?;
+    if pins1.len() != pins2.len() {
+        return Err(NetlistError::BusWidthMismatch(entry.to_string()));
+    }
+    Ok(pins1.into_iter().zip(pins2).collect())
+}
+
+// parses `c1::p1,c2::p2 as p3`, the leading expose and trailing ; of pcb! syntax are allowed.
+// For buses, this gives the shorted pins for each of the exposed pins of the bus
+fn parse_expose(entry: &str) -> Result<Vec<(Vec<ChipPin>, &'static str)>, NetlistError> {
+    let syntax_err = || NetlistError::Syntax(entry.to_string());
+    let trimmed = entry.trim().trim_end_matches(';');
+    let trimmed = trimmed.strip_prefix("expose ").unwrap_or(trimmed);
+    let (pins, as_name) = trimmed.rsplit_once(" as ").ok_or_else(syntax_err)?;
+    let as_names = parse_pin_names(as_name).ok_or_else(syntax_err)?;
+    let mut exposed: Vec<(Vec<ChipPin>, &'static str)> = as_names
+        .iter()
+        .map(|as_name| (Vec::new(), intern(as_name)))
+        .collect();
+    for pin in pins.split(',') {
+        let pins = parse_chip_pins(pin).ok_or_else(syntax_err)?;
+        if pins.len() != exposed.len() {
+            return Err(NetlistError::BusWidthMismatch(entry.to_string()));
+        }
+        for ((shorted, _), pin) in exposed.iter_mut().zip(pins) {
+            shorted.push(pin);
+        }
+    }
+    Ok(exposed)
+}
+
+impl Netlist {
+    pub fn from_json(s: &str) -> Result<Self, NetlistError> {
+        serde_json::from_str(s).map_err(|e| NetlistError::Parse(e.to_string()))
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, NetlistError> {
+        toml::from_str(s).map_err(|e| NetlistError::Parse(e.to_string()))
+    }
+
+    /// constructs the chips using the registry, and builds the pcb using DynamicPcbBuilder,
+    /// so the pcb is validated the same way as the pcb! generated ones
+    pub fn build(&self, registry: &ChipRegistry) -> Result<DynamicPcb, NetlistError> {
+        let mut builder = DynamicPcbBuilder::new();
+        for (chip, chip_type) in &self.chips {
+            match registry.construct(chip_type) {
+                Some(c) => builder = builder.add_chip(chip, c),
+                None => {
+                    return Err(NetlistError::UnknownChipType {
+                        chip: chip.clone(),
+                        chip_type: chip_type.clone(),
+                    })
+                }
+            }
+        }
+        for entry in &self.connections {
+            for (pin1, pin2) in parse_connection(entry)? {
+                builder = builder.connect(pin1, pin2);
+            }
+        }
+        for entry in &self.expose {
+            for (pins, as_name) in parse_expose(entry)? {
+                builder = builder.expose(&pins, as_name);
+            }
+        }
+        builder.build().map_err(NetlistError::Build)
+    }
+}
@@ -0,0 +1,87 @@
+use super::*;
+
+/// This is what the destination pins of a tristated group get when none of its pins are active.
+/// Without a pull, logic pins get Z, and pins of other types keep the values they have
+#[derive(Debug)]
+pub enum Pull {
+    /// the pins get the value, as with a pull-up or pull-down resistor. This is created
+    /// using Pull::value, which also stores the types of pins the value can be given to
+    Value {
+        value: PinValue,
+        type_ids: [TypeId; 2],
+        data_type: &'static str,
+    },
+    /// the pins keep the last value driven on the bus, as with a bus keeper. This is
+    /// same as not having a pull for pins which are not logic, and keeps logic pins from floating to Z
+    Keeper,
+}
+
+impl Pull {
+    /// gives the pull to the given value, which can be given to pins of type T or Option<T>,
+    /// so for example pulling up an `Option<u8>` bus is `Pull::value(0xFF_u8)`
+    pub fn value<T: PinData + 'static>(val: T) -> Self {
+        Pull::Value {
+            value: val.to_pin_value(),
+            type_ids: [TypeId::of::<T>(), TypeId::of::<Option<T>>()],
+            data_type: std::any::type_name::<T>(),
+        }
+    }
+}
+
+/// Sets the pulls of the tristated groups : a group gets the pull given for any of its pins, where
+/// later pulls override earlier ones. Gives an error for the pins which are not in any tristated group,
+/// as other pins are always driven, and for the values which cannot be given to the pin
+pub fn set_pulls(
+    connections: &mut [ResolvedPins],
+    chips: &[Box<PcbChip>],
+    pulls: Vec<(ChipPin, Pull)>,
+    errors: &mut Vec<BuildError>,
+) {
+    for (pin, p) in pulls {
+        let group = connections.iter_mut().find_map(|c| match c {
+            ResolvedPins::Tristated {
+                sources,
+                destinations,
+                pull,
+                ..
+            } => sources
+                .iter()
+                .chain(destinations.iter())
+                .find(|s| s.name == pin)
+                .map(|s| (s.chip, pull)),
+            _ => None,
+        });
+        let (chip, pull) = match group {
+            Some(group) => group,
+            None => {
+                errors.push(BuildError::NotTristated(pin));
+                continue;
+            }
+        };
+        if let Pull::Value {
+            type_ids,
+            data_type,
+            ..
+        } = &p
+        {
+            let md = chips[chip].get_pin_list()[pin.pin];
+            if !type_ids.contains(&md.type_id) {
+                errors.push(BuildError::PullTypeMismatch {
+                    pin: (pin, md),
+                    data_type,
+                });
+                continue;
+            }
+        }
+        *pull = Some(p);
+    }
+}
+
+/// forgets the values kept by the bus keepers, used by the pcbs when they are reset
+pub fn clear_keepers(connections: &mut [ResolvedPins]) {
+    for connection in connections {
+        if let ResolvedPins::Tristated { kept, .. } = connection {
+            *kept = None;
+        }
+    }
+}
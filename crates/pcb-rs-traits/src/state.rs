@@ -0,0 +1,109 @@
+use super::*;
+use std::collections::BTreeMap;
+
+/// This is the saved state of a chip, as given by ChipInterface::save_state. It stores the
+/// values of the pins by the pin names, the values of other state, such as fields marked #[state]
+/// in Chip derived structs, by their names, and the states of the component chips in case of pcbs.
+/// With the `serde` feature, this can be serialized in any serde format, as long as none of the
+/// values are PinValue::Custom
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChipState {
+    pub pins: BTreeMap<String, PinValue>,
+    pub state: BTreeMap<String, PinValue>,
+    pub chips: BTreeMap<String, ChipState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// This is the error given when restoring a saved state fails. The paths are
+/// the names of chips from the outermost pcb separated by /, such as `inner/c1/pin1`
+pub enum StateError {
+    /// the state has a chip which is not present in the pcb
+    UnknownChip(String),
+    /// the value in the state could not be restored
+    InvalidValue { path: String, error: PinValueError },
+}
+
+impl StateError {
+    /// prefixes the path of the error with given chip name, used by pcbs for errors of their chips
+    pub fn in_chip(self, chip: &str) -> Self {
+        match self {
+            StateError::UnknownChip(path) => StateError::UnknownChip(format!("{}/{}", chip, path)),
+            StateError::InvalidValue { path, error } => StateError::InvalidValue {
+                path: format!("{}/{}", chip, path),
+                error,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::UnknownChip(path) => {
+                write!(f, "chip {} in the saved state is not found", path)
+            }
+            StateError::InvalidValue { path, error } => {
+                write!(f, "cannot restore value of {} : {}", path, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+// these are the default implementations of ChipInterface::save_state and restore_state,
+// which only store the pins. The Chip derive uses these for the pins of chips with #[state]
+
+/// saves the values of all the pins of the chip, by the pin names
+pub fn save_pins<C: ChipInterface + ?Sized>(chip: &C) -> ChipState {
+    let mut state = ChipState::default();
+    for pin in chip.get_pin_list().into_keys() {
+        if let Some(val) = chip.get_pin_value(pin) {
+            state.pins.insert(pin.to_string(), val);
+        }
+    }
+    state
+}
+
+/// restores the values of the pins saved by save_pins
+pub fn restore_pins<C: ChipInterface + ?Sized>(
+    chip: &mut C,
+    state: &ChipState,
+) -> Result<(), StateError> {
+    for (name, val) in &state.pins {
+        chip.set_pin_value(name, val)
+            .map_err(|error| StateError::InvalidValue {
+                path: name.clone(),
+                error,
+            })?;
+    }
+    Ok(())
+}
+
+// pcbs store the states of their chips instead, as their pins are the pins of the chips
+
+/// saves the states of the chips of a pcb, used by the pcbs to implement ChipInterface::save_state.
+/// The chip names are in the same order as the chips
+pub fn save_chips(names: &[String], chips: &[Box<PcbChip>]) -> ChipState {
+    let mut state = ChipState::default();
+    for (name, chip) in names.iter().zip(chips) {
+        state.chips.insert(name.clone(), chip.save_state());
+    }
+    state
+}
+
+/// restores the states of the chips of a pcb, used by the pcbs to implement ChipInterface::restore_state
+pub fn restore_chips(
+    names: &[String],
+    chips: &mut [Box<PcbChip>],
+    state: &ChipState,
+) -> Result<(), StateError> {
+    for (name, s) in &state.chips {
+        match names.iter().position(|n| n == name) {
+            Some(i) => chips[i].restore_state(s).map_err(|e| e.in_chip(name))?,
+            None => return Err(StateError::UnknownChip(name.clone())),
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,25 @@
 use super::*;
 
+// io pins are present in both input and output, so we skip them the second time
+fn get_group_pins(
+    input: Vec<(ChipPin, &PinMetadata)>,
+    output: Vec<(ChipPin, &PinMetadata)>,
+) -> Vec<(ChipPin, PinMetadata)> {
+    let mut ret: Vec<(ChipPin, PinMetadata)> = Vec::with_capacity(input.len() + output.len());
+    for (pin, md) in output.into_iter().chain(input) {
+        if !ret.iter().any(|(p, _)| *p == pin) {
+            ret.push((pin, *md));
+        }
+    }
+    ret
+}
+
+// this is only called when building the pcb, so the size of the error does not matter much
+#[allow(clippy::result_large_err)]
 pub fn get_pin_group(
     input: Vec<(ChipPin, &PinMetadata)>,
     output: Vec<(ChipPin, &PinMetadata)>,
-) -> Result<ConnectedPins, String> {
+) -> Result<ConnectedPins, BuildError> {
     // super basic, there is a single input pin and single output pin
     if input.len() == 1 && output.len() == 1 {
         return Ok(ConnectedPins::Pair {
@@ -21,29 +37,14 @@ pub fn get_pin_group(
 
     // here, there are multiple output pins, and not all are tristated
     if !all_tristatable && output.len() > 1 {
-        let temp: Vec<&PinMetadata> = output
-            .into_iter()
-            .chain(input.into_iter())
-            .map(|(_, md)| md)
-            .collect();
-        return Result::Err(format!(
-                "multiple output pins found in a non-tristated pin group : {:#?}\nOnly groups where all pins are tristatable are allowed to have multiple output pins"
-                ,temp));
+        return Result::Err(BuildError::MultipleDrivers(get_group_pins(input, output)));
     }
 
     // I'm not sure if this condition can ever occur, as we check each connected pin pairing,
     // and tristatable mark is like a colour, so that only tristatable pin can be connected to tristatable pins
     // but it is put here as a safety measure
     if !all_tristatable && any_tristatable {
-        let temp: Vec<&PinMetadata> = output
-            .into_iter()
-            .chain(input.into_iter())
-            .map(|(_, md)| md)
-            .collect();
-        return Result::Err(format!(
-                "these pins are shorted, but not all are tristatable : {:#?}\nIf any pin a a shourted pin group is tristatable, then all must be tristatable"
-                ,temp
-            ));
+        return Result::Err(BuildError::MixedTristate(get_group_pins(input, output)));
     }
 
     // single output pin connected to multiple input pins, this is a broadcast
@@ -59,3 +60,313 @@ pub fn get_pin_group(
         destinations: input.into_iter().map(|(p, _)| p).collect(),
     })
 }
+
+/// Ticks the chips of a pcb whose clocks tick on the given cycle, in the given order, on the given edge using their try_tick_edge.
+/// With the `parallel` feature, the chips are instead ticked concurrently on the rayon thread pool, as the
+/// chips only exchange values after all of them are ticked, so the ticks of chips do not depend on each other.
+/// The chip names and clocks are in the same order as the chips, and the names are used for the paths in the errors
+pub fn tick_chips(
+    chips: &mut [Box<PcbChip>],
+    names: &[String],
+    clocks: &[Clock],
+    cycle: u64,
+    edge: ClockEdge,
+) -> Result<(), SimError> {
+    #[cfg(not(feature = "parallel"))]
+    for ((chip, name), clock) in chips.iter_mut().zip(names).zip(clocks) {
+        if clock.ticks_at(cycle) {
+            chip.try_tick_edge(edge).map_err(|e| e.in_chip(name))?;
+        }
+    }
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        chips
+            .par_iter_mut()
+            .zip(names)
+            .zip(clocks)
+            .filter(|(_, clock)| clock.ticks_at(cycle))
+            .try_for_each(|((chip, name), _)| {
+                chip.try_tick_edge(edge).map_err(|e| e.in_chip(name))
+            })?;
+    }
+    Ok(())
+}
+
+// the pins are validated when building the pcb, but manually implemented chips
+// can still fail to give or take the values, so these give errors for those
+
+fn get_value(chips: &[Box<PcbChip>], slot: &PinSlot) -> Result<PinValue, SimError> {
+    slot.pin
+        .get_value(chips[slot.chip].as_ref())
+        .ok_or_else(|| SimError::MissingValue(format!("{}/{}", slot.name.chip, slot.name.pin)))
+}
+
+fn set_value(
+    chips: &mut [Box<PcbChip>],
+    slot: &PinSlot,
+    val: &PinValue,
+) -> Result<(), SimError> {
+    slot.pin
+        .set_value(chips[slot.chip].as_mut(), val)
+        .map_err(|error| SimError::InvalidValue {
+            path: format!("{}/{}", slot.name.chip, slot.name.pin),
+            error,
+        })
+}
+
+/// These are the buffers used by propagate_pin_values and settle_combinational_chips, which the pcbs
+/// keep between the ticks, so that passing the values does not allocate on every tick
+#[derive(Default)]
+pub struct ScratchBuffers {
+    // positions of the pins driving a tristated group, in its sources
+    drivers: Vec<usize>,
+    // values of the pins of a combinational chip before it was re-ticked
+    previous: Vec<PinValue>,
+}
+
+/// Gives the values of source pins of each connected pin group to the respective destination pins.
+/// This is used by the pcbs to pass on pin values after ticking the chips, and assumes that
+/// the chips and pins in the connections have already been validated when building the pcb.
+/// The contentions in tristated groups with Error or Unknown policy are added to contentions,
+/// and the ones with Panic policy are given as an error, as the chips would burn.
+/// The connections are mutable as the groups with bus keepers store the last value driven on them
+pub fn propagate_pin_values(
+    chips: &mut [Box<PcbChip>],
+    connections: &mut [ResolvedPins],
+    contentions: &mut Vec<Contention>,
+    scratch: &mut ScratchBuffers,
+) -> Result<(), SimError> {
+    let drivers = &mut scratch.drivers;
+    for connection in connections {
+        match connection {
+            ResolvedPins::Pair {
+                source,
+                destination,
+            } => {
+                // this is simplest, as there is a single input and single output pin,
+                // both of which are of respective types, so even if they're tristated,
+                //  their data types will match, and there won't be an issue
+                let val = get_value(chips, source)?;
+                set_value(chips, destination, &val)?;
+            }
+            ResolvedPins::Broadcast {
+                source,
+                destinations,
+            } => {
+                // now this can get tricky, as the source pin might be of type
+                // io, so it can be present in destinations as well, so we have to skip it
+                // as well as check that if there is any destination pin that is
+                // io type, then it is set to input mode
+                // also we do not check if the source pin, if of io type
+                // is set to input mode or not, the destination pins will get
+                // whatever its value is regardless
+                let val = get_value(chips, source)?;
+                for dest in destinations {
+                    if dest == source {
+                        // accounts for the io type source pin
+                        continue;
+                    }
+                    // we don't have to check if any other pin is of io type, because if it was
+                    // then taht set-up would be in the tristated group
+                    set_value(chips, dest, &val)?;
+                }
+            }
+            ResolvedPins::Tristated {
+                sources,
+                destinations,
+                policy,
+                pull,
+                kept,
+            } => {
+                let mut val: Option<PinValue> = None;
+                drivers.clear();
+                let mut conflict = false;
+                for (i, src) in sources.iter().enumerate() {
+                    let chip = chips[src.chip].as_ref();
+                    // input mode check if specifically for io pins, which would be present in
+                    // both sources and destinations, and if one want to get the data in io pin
+                    // the pin must not be in tristated mode, but must be in input mode
+                    if !src.pin.in_input_mode(chip) && !src.pin.is_tristated(chip) {
+                        let src_val = get_value(chips, src)?;
+                        val = match val {
+                            None => Some(src_val),
+                            Some(v) => {
+                                conflict |= v.conflicts(&src_val);
+                                // logic pins driven by multiple chips are resolved, which gives X for
+                                // different levels, other types cannot be resolved, so what happens
+                                // depends on the contention policy
+                                let pins = drivers.iter().chain([&i]).map(|d| sources[*d].name);
+                                match contention::combine(*policy, &v, &src_val, pins)? {
+                                    Some(combined) => Some(combined),
+                                    // the tick of the pcb panics with this error, as the chips would burn
+                                    None if *policy == ContentionPolicy::Panic => {
+                                        return Err(SimError::Contention {
+                                            path: String::new(),
+                                            contention: Contention {
+                                                drivers: drivers
+                                                    .iter()
+                                                    .chain([&i])
+                                                    .map(|d| sources[*d].name)
+                                                    .collect(),
+                                                policy: *policy,
+                                            },
+                                        });
+                                    }
+                                    // we keep the first value, which is not given to the destinations anyway
+                                    None => Some(v),
+                                }
+                            }
+                        };
+                        drivers.push(i);
+                    }
+                }
+                if conflict && matches!(policy, ContentionPolicy::Error | ContentionPolicy::Unknown)
+                {
+                    contentions.push(Contention {
+                        drivers: drivers.iter().map(|d| sources[*d].name).collect(),
+                        policy: *policy,
+                    });
+                    // values which are not logic do not have an unknown value, so the
+                    // destinations keep their previous values
+                    if !val.as_ref().is_some_and(|v| v.is_logic()) {
+                        continue;
+                    }
+                }
+                // when nothing drives the bus, the pins get the pull value if the group has one, or the
+                // last value driven on the bus with a keeper. Without a pull, logic pins get Z. Other types
+                // do not have such a value, so their pins keep the values they have
+                if drivers.is_empty() {
+                    match pull {
+                        Some(Pull::Value { value, .. }) => {
+                            drive_destinations(chips, sources, destinations, drivers, value)?;
+                            continue;
+                        }
+                        Some(Pull::Keeper) => {
+                            if let Some(kept) = kept {
+                                drive_destinations(chips, sources, destinations, drivers, kept)?;
+                            }
+                            continue;
+                        }
+                        None => {
+                            val = sources.first().and_then(|src| {
+                                src.pin
+                                    .get_value(chips[src.chip].as_ref())
+                                    .and_then(|v| v.to_high_z())
+                            });
+                        }
+                    }
+                }
+                if let Some(val) = val {
+                    drive_destinations(chips, sources, destinations, drivers, &val)?;
+                    if !drivers.is_empty() && matches!(pull, Some(Pull::Keeper)) {
+                        *kept = Some(val);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// gives the value on a tristated bus to the pins reading it, which are the input pins, and the
+// io pins in input mode. Io pins which are not in input mode are either driving the bus, or are
+// tristated, in which case setting the value would make them drive it on the next tick
+fn drive_destinations(
+    chips: &mut [Box<PcbChip>],
+    sources: &[PinSlot],
+    destinations: &[PinSlot],
+    drivers: &[usize],
+    val: &PinValue,
+) -> Result<(), SimError> {
+    for dest in destinations {
+        // skip in case the pin is io type and present in both source and destinations
+        if drivers.iter().any(|d| sources[*d] == *dest) {
+            continue;
+        }
+        if sources.contains(dest) && !dest.pin.in_input_mode(chips[dest.chip].as_ref()) {
+            continue;
+        }
+        set_value(chips, dest, val)?;
+    }
+    Ok(())
+}
+
+/// This collects the positions of combinational chips, along with their output and io pins,
+/// which are the pins we need to compare to know if the chip has settled
+pub fn get_combinational_chips(chips: &[Box<PcbChip>]) -> Vec<(usize, Vec<PinIndex>)> {
+    chips
+        .iter()
+        .enumerate()
+        .filter(|(_, chip)| chip.is_combinational())
+        .map(|(i, chip)| {
+            let mut pins: Vec<_> = chip
+                .get_pin_list()
+                .into_iter()
+                .filter(|(_, md)| !matches!(md.pin_type, PinType::Input))
+                .map(|(pin, _)| pin)
+                .collect();
+            // pin list is a hashmap, so we sort the pins to keep the order same across runs
+            pins.sort_unstable();
+            let pins = pins
+                .into_iter()
+                .map(|pin| get_pin_index(chip.as_ref(), pin))
+                .collect();
+            (i, pins)
+        })
+        .collect()
+}
+
+/// Re-ticks the combinational chips and propagates the pin values until the
+/// output pins of combinational chips stop changing. Gives an error if they do not settle,
+/// the names of chips are used for the paths in the errors
+pub fn settle_combinational_chips(
+    chips: &mut [Box<PcbChip>],
+    names: &[String],
+    connections: &mut [ResolvedPins],
+    combinational_chips: &[(usize, Vec<PinIndex>)],
+    contentions: &mut Vec<Contention>,
+    scratch: &mut ScratchBuffers,
+) -> Result<(), SimError> {
+    if combinational_chips.is_empty() {
+        return Ok(());
+    }
+    // each pass carries a change at least one chip further, so an acyclic group of
+    // n combinational chips settles within n passes. We allow twice that so feedback
+    // circuits which do settle (such as latches) get enough passes, anything which
+    // still changes after that is considered an oscillating loop
+    let max_passes = 2 * combinational_chips.len() + 1;
+    for _ in 0..max_passes {
+        let mut changed = false;
+        for (i, pins) in combinational_chips {
+            let chip = chips[*i].as_mut();
+            let previous = &mut scratch.previous;
+            previous.clear();
+            for pin in pins {
+                let val = pin.get_value(chip).ok_or_else(|| {
+                    let pin = match pin {
+                        PinIndex::Index(index) => index.to_string(),
+                        PinIndex::Name(name) => name.to_string(),
+                    };
+                    SimError::MissingValue(format!("{}/{}", names[*i], pin))
+                })?;
+                previous.push(val);
+            }
+            chip.try_tick().map_err(|e| e.in_chip(&names[*i]))?;
+            for (pin, val) in pins.iter().zip(previous.iter()) {
+                if !pin.value_eq(chip, val) {
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Ok(());
+        }
+        propagate_pin_values(chips, connections, contentions, scratch)?;
+    }
+    let names = combinational_chips
+        .iter()
+        .map(|(i, _)| names[*i].clone())
+        .collect();
+    Err(SimError::Unsettled(names))
+}
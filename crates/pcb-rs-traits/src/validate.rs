@@ -0,0 +1,190 @@
+use super::*;
+
+// These are the validations done when building the pcbs, used by both the DynamicPcbBuilder and the
+// builders generated by pcb!. The pcb! generated builders give the connections and exposed pins
+// declared in the macro, which are already checked for what can be checked when compiling
+
+/// gives all the pins used in the pcb, in connections, exposed pins, contention policies and pulls,
+/// without duplicates
+pub fn used_pins(
+    connections: &[(ChipPin, ChipPin)],
+    exposed_pins: &[(Vec<ChipPin>, &'static str)],
+    contention_policies: &[(ChipPin, ContentionPolicy)],
+    pulls: &[(ChipPin, Pull)],
+) -> Vec<ChipPin> {
+    let mut ret: Vec<ChipPin> = Vec::new();
+    let connected = connections.iter().flat_map(|(p1, p2)| [*p1, *p2]);
+    let exposed = exposed_pins
+        .iter()
+        .flat_map(|(pins, _)| pins.iter().copied());
+    let policies = contention_policies.iter().map(|(pin, _)| *pin);
+    let pulls = pulls.iter().map(|(pin, _)| *pin);
+    for pin in connected.chain(exposed).chain(policies).chain(pulls) {
+        if !ret.contains(&pin) {
+            ret.push(pin);
+        }
+    }
+    ret
+}
+
+/// checks that the given chips, and the chips of the given pins are added, giving an error once for
+/// each missing chip
+pub fn check_added_chips(
+    chips: &HashMap<String, Box<PcbChip>>,
+    names: &[&'static str],
+    pins: &[ChipPin],
+    errors: &mut Vec<BuildError>,
+) {
+    let mut missing = Vec::new();
+    for chip in names.iter().copied().chain(pins.iter().map(|pin| pin.chip)) {
+        if !chips.contains_key(chip) && !missing.contains(&chip) {
+            missing.push(chip);
+            errors.push(BuildError::MissingChip(chip));
+        }
+    }
+}
+
+/// checks that the chips have the given pins, and that the combinational chips are not put in
+/// clock domains, as they are settled on every tick. Missing chips are skipped, as those are
+/// reported by check_added_chips
+pub fn check_valid_chips(
+    chips: &HashMap<String, Box<PcbChip>>,
+    pins: &[ChipPin],
+    clocks: &[(&'static str, Clock)],
+    errors: &mut Vec<BuildError>,
+) {
+    for pin in pins {
+        if let Some(chip) = chips.get(pin.chip) {
+            if !chip.get_pin_list().contains_key(pin.pin) {
+                errors.push(BuildError::UnknownPin(*pin));
+            }
+        }
+    }
+    for (chip, clock) in clocks {
+        if *clock != Clock::MAIN && chips.get(*chip).is_some_and(|c| c.is_combinational()) {
+            errors.push(BuildError::CombinationalClock(chip));
+        }
+    }
+}
+
+/// gives the metadata of the given pins, which must have been checked using check_valid_chips
+pub fn get_pin_metadata(
+    chips: &HashMap<String, Box<PcbChip>>,
+    pins: &[ChipPin],
+) -> HashMap<ChipPin, PinMetadata> {
+    let mut ret = HashMap::new();
+    for pin in pins {
+        let md = chips[pin.chip].get_pin_list()[pin.pin];
+        ret.insert(*pin, md);
+    }
+    ret
+}
+
+/// checks that the pins of each connection can be connected, see PinMetadata::is_connectable
+pub fn check_pin_connections(
+    connections: &[(ChipPin, ChipPin)],
+    pin_metadata: &HashMap<ChipPin, PinMetadata>,
+    errors: &mut Vec<BuildError>,
+) {
+    for (pin1, pin2) in connections {
+        let md1 = pin_metadata[pin1];
+        let md2 = pin_metadata[pin2];
+        if !md1.is_connectable(&md2) {
+            errors.push(BuildError::IncompatiblePins {
+                first: (*pin1, md1),
+                second: (*pin2, md2),
+            });
+        }
+    }
+}
+
+/// groups the connected pins into the sets of pins that are shorted, i.e. connected electrically,
+/// so that the value of any one of the pins in a group affects rest of the pins in it
+pub fn get_short_pin_set(connections: &[(ChipPin, ChipPin)]) -> Vec<Vec<ChipPin>> {
+    let mut shorted_pins: Vec<Vec<ChipPin>> = Vec::new();
+    for (pin1, pin2) in connections {
+        // the groups containing either of the pins get merged with the connection,
+        // rest of the groups stay as they are
+        let mut set = vec![*pin1, *pin2];
+        let mut t = Vec::with_capacity(shorted_pins.len());
+        for group in shorted_pins {
+            if group.contains(pin1) || group.contains(pin2) {
+                for pin in group {
+                    if !set.contains(&pin) {
+                        set.push(pin);
+                    }
+                }
+            } else {
+                t.push(group);
+            }
+        }
+        t.push(set);
+        shorted_pins = t;
+    }
+    shorted_pins
+}
+
+/// checks that the pins exposed together are not connected to other pins, and are input pins
+/// of the same type
+pub fn check_exposed_pins(
+    exposed_pins: &[(Vec<ChipPin>, &'static str)],
+    shorted_pins: &[Vec<ChipPin>],
+    pin_metadata: &HashMap<ChipPin, PinMetadata>,
+    errors: &mut Vec<BuildError>,
+) {
+    for (pins, as_name) in exposed_pins {
+        if pins.len() == 1 {
+            // ignore the checks for non-shorted exposed pins
+            continue;
+        }
+        if pins
+            .iter()
+            .any(|pin| shorted_pins.iter().any(|sp| sp.contains(pin)))
+        {
+            errors.push(BuildError::ExposedShortConnected {
+                as_name,
+                pins: pins.clone(),
+            });
+        }
+        let first = (pins[0], pin_metadata[&pins[0]]);
+        for pin in pins {
+            let pin = (*pin, pin_metadata[pin]);
+            if !matches!(pin.1.pin_type, PinType::Input) {
+                errors.push(BuildError::ExposedShortNotInput { as_name, pin });
+            }
+            if pin.1.type_id != first.1.type_id {
+                errors.push(BuildError::ExposedShortTypeMismatch {
+                    as_name,
+                    pin,
+                    expected: first,
+                });
+            }
+        }
+    }
+}
+
+/// gives the ConnectedPins for each group of shorted pins, see get_pin_group
+pub fn get_connected_pins(
+    shorted_pins: &[Vec<ChipPin>],
+    pin_metadata: &HashMap<ChipPin, PinMetadata>,
+    errors: &mut Vec<BuildError>,
+) -> Vec<ConnectedPins> {
+    let mut ret = Vec::with_capacity(shorted_pins.len());
+    for group in shorted_pins {
+        let input_pins = group
+            .iter()
+            .map(|pin| (*pin, &pin_metadata[pin]))
+            .filter(|(_, md)| matches!(md.pin_type, PinType::Input | PinType::IO))
+            .collect();
+        let output_pins = group
+            .iter()
+            .map(|pin| (*pin, &pin_metadata[pin]))
+            .filter(|(_, md)| matches!(md.pin_type, PinType::Output | PinType::IO))
+            .collect();
+        match get_pin_group(input_pins, output_pins) {
+            Ok(group) => ret.push(group),
+            Err(e) => errors.push(e),
+        }
+    }
+    ret
+}
@@ -0,0 +1,592 @@
+use super::logic::{self, Logic, LogicVec};
+use std::any::{Any, TypeId};
+use std::marker::PhantomData;
+
+/// This is the value of a pin, as passed around between the chips by the encompassing
+/// module. The commonly used data types have their own variants, so passing those
+/// does not need any allocation. Values of any other data type are boxed as Custom.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PinValue {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(usize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(isize),
+    /// a bit vector such as [bool; N], where the first element is the least significant bit
+    Bits {
+        bits: u128,
+        width: u8,
+    },
+    /// a tristated pin, i.e. an Option pin which is None. The kind and width are of the value
+    /// when the pin is not tristated, so the value is only read back as Option of the same type
+    HighZ {
+        kind: ValueKind,
+        width: u8,
+    },
+    /// levels of Logic and LogicVec pins, where the first element is the least significant bit.
+    /// x and z have the bits which are X or Z, and bits has the bits which are 1
+    Logic {
+        bits: u128,
+        x: u128,
+        z: u128,
+        width: u8,
+    },
+    /// value of any other data type. These cannot be serialized, as the type is not known
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Box<CustomValue>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// This is the variant of PinValue which a tristated pin has when it is not tristated
+pub enum ValueKind {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    Bits,
+}
+
+/// This is the type of values in PinValue::Custom. With the `parallel` feature, the pcbs are ticked
+/// on multiple threads along with the values they store, such as pulls, so the values must be Send
+#[cfg(not(feature = "parallel"))]
+pub type CustomValue = dyn Any;
+
+/// This is the type of values in PinValue::Custom, see the other definition
+#[cfg(feature = "parallel")]
+pub type CustomValue = dyn Any + Send;
+
+/// This is implemented for all the types which can be boxed as CustomValue, i.e. all 'static types,
+/// which are also Send with the `parallel` feature. The Chip derive uses this as the bound for the
+/// pins of generic chips
+#[cfg(not(feature = "parallel"))]
+pub trait CustomPinData: Any {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: Any> CustomPinData for T {}
+
+/// This is implemented for all the types which can be boxed as CustomValue, see the other definition
+#[cfg(feature = "parallel")]
+pub trait CustomPinData: Any + Send {}
+
+#[cfg(feature = "parallel")]
+impl<T: Any + Send> CustomPinData for T {}
+
+/// This is implemented by the data types which have their own variants in PinValue,
+/// and for Option of those. The Chip derive uses this for pins of these types,
+/// and boxes the values of all other types as PinValue::Custom
+pub trait PinData: Sized {
+    /// number of bits in the value
+    const WIDTH: u8;
+
+    /// converts the value to a PinValue
+    fn to_pin_value(&self) -> PinValue;
+
+    /// converts a PinValue back to the type, gives None if the value is of another type
+    fn from_pin_value(val: &PinValue) -> Option<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// This is the error given when a pin value cannot be set
+pub enum PinValueError {
+    /// the chip does not have a pin with the given name
+    UnknownPin(String),
+    /// the value given is not of the data type of the pin
+    TypeMismatch {
+        pin: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl std::fmt::Display for PinValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PinValueError::UnknownPin(pin) => write!(f, "no pin named {} found", pin),
+            PinValueError::TypeMismatch { pin, expected } => write!(
+                f,
+                "value given to pin {} is of incorrect type, expected {}",
+                pin, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PinValueError {}
+
+impl PinValue {
+    /// gives the value as the given type, if it is of that type
+    pub fn get<T: PinData>(&self) -> Option<T> {
+        T::from_pin_value(self)
+    }
+
+    /// gives a reference to the value, if it is of the given type. This is meant for
+    /// Custom values, but also works for values of types which have their own variants
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        let val: &dyn Any = match self {
+            PinValue::Bool(v) => v,
+            PinValue::U8(v) => v,
+            PinValue::U16(v) => v,
+            PinValue::U32(v) => v,
+            PinValue::U64(v) => v,
+            PinValue::U128(v) => v,
+            PinValue::Usize(v) => v,
+            PinValue::I8(v) => v,
+            PinValue::I16(v) => v,
+            PinValue::I32(v) => v,
+            PinValue::I64(v) => v,
+            PinValue::I128(v) => v,
+            PinValue::Isize(v) => v,
+            PinValue::Custom(v) => v.as_ref(),
+            PinValue::Bits { .. } | PinValue::HighZ { .. } | PinValue::Logic { .. } => return None,
+        };
+        val.downcast_ref()
+    }
+
+    /// gives if this is the value of a Logic or LogicVec pin
+    pub fn is_logic(&self) -> bool {
+        matches!(self, PinValue::Logic { .. })
+    }
+
+    /// gives the level of a bus driven by both the values, for Logic and LogicVec values of the
+    /// same width, see Logic::resolve. Values of other types cannot be resolved, so this gives None
+    pub fn resolve(&self, other: &PinValue) -> Option<PinValue> {
+        self.zip_levels(other, Logic::resolve)
+    }
+
+    /// gives if both the values drive the bus to different levels. For Logic and LogicVec values this
+    /// is when any bit is driven by both, and the levels are not the same known level. Values of other
+    /// types do not have undriven bits, so driving the bus with both of them is always a conflict
+    pub fn conflicts(&self, other: &PinValue) -> bool {
+        match self.zip_levels(other, |l1, l2| {
+            if l1.is_z() || l2.is_z() || (l1 == l2 && l1.is_known()) {
+                Logic::Zero
+            } else {
+                Logic::One
+            }
+        }) {
+            Some(PinValue::Logic { bits, .. }) => bits != 0,
+            _ => true,
+        }
+    }
+
+    /// gives the bitwise and of the values, as driven on a wired-and bus. This works for bool,
+    /// integer, bit vector and logic values of the same type, where the Z levels of logic values
+    /// are not driven, and thus do not affect the result. Gives None for values of other types
+    pub fn wired_and(&self, other: &PinValue) -> Option<PinValue> {
+        self.bitwise(
+            other,
+            |a, b| a & b,
+            |l1, l2| match (l1, l2) {
+                (Logic::Z, l) | (l, Logic::Z) => l,
+                (l1, l2) => l1 & l2,
+            },
+        )
+    }
+
+    /// gives the bitwise or of the values, as driven on a wired-or bus, same as wired_and
+    pub fn wired_or(&self, other: &PinValue) -> Option<PinValue> {
+        self.bitwise(
+            other,
+            |a, b| a | b,
+            |l1, l2| match (l1, l2) {
+                (Logic::Z, l) | (l, Logic::Z) => l,
+                (l1, l2) => l1 | l2,
+            },
+        )
+    }
+
+    // the integer values are combined as u128, as the bitwise ops are same for all of these,
+    // and the result is cast back to the type, which truncates the extra bits
+    fn bitwise(
+        &self,
+        other: &PinValue,
+        op: fn(u128, u128) -> u128,
+        level_op: fn(Logic, Logic) -> Logic,
+    ) -> Option<PinValue> {
+        macro_rules! int_ops {
+            ($($variant:ident => $t:ty),*) => {
+                match (self, other) {
+                    (PinValue::Bool(a), PinValue::Bool(b)) => {
+                        Some(PinValue::Bool(op(*a as u128, *b as u128) != 0))
+                    }
+                    $(
+                        (PinValue::$variant(a), PinValue::$variant(b)) => {
+                            Some(PinValue::$variant(op(*a as u128, *b as u128) as $t))
+                        }
+                    )*
+                    (PinValue::Bits { bits: a, width }, PinValue::Bits { bits: b, width: w2 })
+                        if width == w2 =>
+                    {
+                        Some(PinValue::Bits {
+                            bits: op(*a, *b),
+                            width: *width,
+                        })
+                    }
+                    _ => self.zip_levels(other, level_op),
+                }
+            };
+        }
+        int_ops!(
+            U8 => u8, U16 => u16, U32 => u32, U64 => u64, U128 => u128, Usize => usize,
+            I8 => i8, I16 => i16, I32 => i32, I64 => i64, I128 => i128, Isize => isize
+        )
+    }
+
+    // combines the levels of Logic values of same width bit by bit, gives None for other values
+    fn zip_levels(&self, other: &PinValue, f: impl Fn(Logic, Logic) -> Logic) -> Option<PinValue> {
+        match (self, other) {
+            (
+                PinValue::Logic {
+                    bits: b1,
+                    x: x1,
+                    z: z1,
+                    width,
+                },
+                PinValue::Logic {
+                    bits: b2,
+                    x: x2,
+                    z: z2,
+                    width: w2,
+                },
+            ) if width == w2 => {
+                let levels: Vec<_> = (0..*width)
+                    .map(|i| {
+                        f(
+                            logic::level_at(*b1, *x1, *z1, i),
+                            logic::level_at(*b2, *x2, *z2, i),
+                        )
+                    })
+                    .collect();
+                let (bits, x, z) = logic::to_masks(&levels);
+                Some(PinValue::Logic {
+                    bits,
+                    x,
+                    z,
+                    width: *width,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// gives the value of an undriven pin of the same type, i.e. all levels Z, for Logic and
+    /// LogicVec values. Other types do not have such a value, so this gives None
+    pub fn to_high_z(&self) -> Option<PinValue> {
+        match self {
+            PinValue::Logic { width, .. } => Some(PinValue::Logic {
+                bits: 0,
+                x: 0,
+                z: 1_u128
+                    .checked_shl(*width as u32)
+                    .unwrap_or(0)
+                    .wrapping_sub(1),
+                width: *width,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl PinData for bool {
+    const WIDTH: u8 = 1;
+
+    fn to_pin_value(&self) -> PinValue {
+        PinValue::Bool(*self)
+    }
+
+    fn from_pin_value(val: &PinValue) -> Option<Self> {
+        match val {
+            PinValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_pin_data_int {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl PinData for $t {
+                const WIDTH: u8 = <$t>::BITS as u8;
+
+                fn to_pin_value(&self) -> PinValue {
+                    PinValue::$variant(*self)
+                }
+
+                fn from_pin_value(val: &PinValue) -> Option<Self> {
+                    match val {
+                        PinValue::$variant(v) => Some(*v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_pin_data_int!(
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128, usize => Usize,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128, isize => Isize
+);
+
+impl<const N: usize> PinData for [bool; N] {
+    // this fails to compile when the array is used as a pin, if it is wider than
+    // what the Bits variant can hold
+    const WIDTH: u8 = {
+        assert!(N <= 128, "bit vector pins can be at most 128 bits wide");
+        N as u8
+    };
+
+    fn to_pin_value(&self) -> PinValue {
+        let bits = self
+            .iter()
+            .enumerate()
+            .fold(0_u128, |acc, (i, b)| acc | ((*b as u128) << i));
+        PinValue::Bits {
+            bits,
+            width: Self::WIDTH,
+        }
+    }
+
+    fn from_pin_value(val: &PinValue) -> Option<Self> {
+        match val {
+            PinValue::Bits { bits, width } if *width == Self::WIDTH => {
+                let mut ret = [false; N];
+                for (i, b) in ret.iter_mut().enumerate() {
+                    *b = bits & (1 << i) != 0;
+                }
+                Some(ret)
+            }
+            _ => None,
+        }
+    }
+}
+
+// We do not implement this for Option<T> in general, as then Option<Option<T>>
+// would have both None and Some(None) converted to HighZ
+macro_rules! impl_pin_data_option {
+    ($($t:ty => $kind:ident),*) => {
+        $(
+            impl PinData for Option<$t> {
+                const WIDTH: u8 = <$t as PinData>::WIDTH;
+
+                fn to_pin_value(&self) -> PinValue {
+                    match self {
+                        Some(v) => v.to_pin_value(),
+                        None => PinValue::HighZ {
+                            kind: ValueKind::$kind,
+                            width: Self::WIDTH,
+                        },
+                    }
+                }
+
+                fn from_pin_value(val: &PinValue) -> Option<Self> {
+                    match val {
+                        PinValue::HighZ { kind, width }
+                            if *kind == ValueKind::$kind && *width == Self::WIDTH =>
+                        {
+                            Some(None)
+                        }
+                        v => <$t as PinData>::from_pin_value(v).map(Some),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_pin_data_option!(
+    bool => Bool, u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128, usize => Usize,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128, isize => Isize
+);
+
+impl<const N: usize> PinData for Option<[bool; N]> {
+    const WIDTH: u8 = <[bool; N] as PinData>::WIDTH;
+
+    fn to_pin_value(&self) -> PinValue {
+        match self {
+            Some(v) => v.to_pin_value(),
+            None => PinValue::HighZ {
+                kind: ValueKind::Bits,
+                width: Self::WIDTH,
+            },
+        }
+    }
+
+    fn from_pin_value(val: &PinValue) -> Option<Self> {
+        match val {
+            PinValue::HighZ { kind, width }
+                if *kind == ValueKind::Bits && *width == Self::WIDTH =>
+            {
+                Some(None)
+            }
+            v => <[bool; N] as PinData>::from_pin_value(v).map(Some),
+        }
+    }
+}
+
+/// Converts the value of a pin whose type is a type parameter, such as the pins of generic chips.
+/// When the concrete type is one of the types implementing PinData, the value is converted through it,
+/// so it can be passed to pins written with that type, otherwise it is boxed as PinValue::Custom.
+/// The Chip derive uses this for the pins whose type uses a type parameter
+pub fn generic_to_pin_value<T: Clone + CustomPinData>(val: &T) -> PinValue {
+    visit_pin_data::<T, _>(&ToPinValue(val))
+        .unwrap_or_else(|| PinValue::Custom(Box::new(val.clone())))
+}
+
+/// Converts the value to the type of a pin whose type is a type parameter, see generic_to_pin_value.
+/// Same as for the pins of PinData types, the Custom values of the type are accepted as well
+pub fn generic_from_pin_value<T: Clone + CustomPinData>(val: &PinValue) -> Option<T> {
+    visit_pin_data::<T, _>(&FromPinValue(val, PhantomData))
+        .flatten()
+        .or_else(|| val.downcast_ref::<T>().cloned())
+}
+
+// a conversion done through the PinData impl of U, which visit_pin_data has found to be the type of the value
+trait PinDataVisitor {
+    type Output;
+    fn visit<U: PinData + Any>(&self) -> Self::Output;
+}
+
+struct ToPinValue<'a, T>(&'a T);
+
+impl<T: Any> PinDataVisitor for ToPinValue<'_, T> {
+    type Output = PinValue;
+    fn visit<U: PinData + Any>(&self) -> PinValue {
+        // T is U, so the downcast cannot fail
+        (self.0 as &dyn Any)
+            .downcast_ref::<U>()
+            .unwrap()
+            .to_pin_value()
+    }
+}
+
+struct FromPinValue<'a, T>(&'a PinValue, PhantomData<T>);
+
+impl<T: Any> PinDataVisitor for FromPinValue<'_, T> {
+    type Output = Option<T>;
+    fn visit<U: PinData + Any>(&self) -> Option<T> {
+        // the value is converted in place, so this does not need to box it to downcast to T
+        let mut ret: Option<T> = None;
+        *(&mut ret as &mut dyn Any)
+            .downcast_mut::<Option<U>>()
+            .unwrap() = U::from_pin_value(self.0);
+        ret
+    }
+}
+
+// calls the visitor with the PinData type which T is, giving None if T is not one of those.
+// The TypeIds and sizes are known when compiling, so after optimization this is only the conversion
+// for the type. Both bool and Logic are a byte wide, so the bit vectors and logic vectors are only
+// checked for the width given by the size of T
+fn visit_pin_data<T: Any, V: PinDataVisitor>(visitor: &V) -> Option<V::Output> {
+    macro_rules! visit {
+        ($($u:ty),*) => {
+            $(
+                if TypeId::of::<T>() == TypeId::of::<$u>() {
+                    return Some(visitor.visit::<$u>());
+                }
+            )*
+        };
+    }
+    macro_rules! visit_widths {
+        ($($n:literal)*) => {
+            match std::mem::size_of::<T>() {
+                $($n => {
+                    visit!([bool; $n], Option<[bool; $n]>, LogicVec<$n>);
+                })*
+                _ => {}
+            }
+        };
+    }
+    macro_rules! visit_with_option {
+        ($($u:ty),*) => {
+            visit!($($u, Option<$u>),*);
+        };
+    }
+    visit_with_option!(bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+    visit!(Logic);
+    visit_widths!(
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+        33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61
+        62 63 64 65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90
+        91 92 93 94 95 96 97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114
+        115 116 117 118 119 120 121 122 123 124 125 126 127 128
+    );
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<T: PinData + PartialEq + std::fmt::Debug + Copy>(v: T) {
+        assert_eq!(v.to_pin_value().get::<T>(), Some(v));
+    }
+
+    #[test]
+    fn values_convert_back_to_their_types() {
+        roundtrip(true);
+        roundtrip(200_u8);
+        roundtrip(-5_i32);
+        roundtrip(u128::MAX);
+        roundtrip(isize::MIN);
+        roundtrip([true, false, true]);
+        roundtrip(Some(7_u16));
+        roundtrip(None::<u16>);
+        roundtrip(Some([false, true]));
+        roundtrip(None::<[bool; 2]>);
+    }
+
+    #[test]
+    fn values_of_other_types_are_not_converted() {
+        assert_eq!(PinValue::U8(1).get::<u16>(), None);
+        assert_eq!(PinValue::U8(1).get::<i8>(), None);
+        assert_eq!(PinValue::Bool(true).get::<u8>(), None);
+        assert_eq!([true; 3].to_pin_value().get::<[bool; 4]>(), None);
+        // tristated values are only read back as Option of the same type
+        let high_z = None::<u8>.to_pin_value();
+        assert_eq!(high_z.get::<u8>(), None);
+        assert_eq!(high_z.get::<Option<u8>>(), Some(None));
+        assert_eq!(high_z.get::<Option<i8>>(), None);
+        assert_eq!(high_z.get::<Option<u16>>(), None);
+        assert_eq!(high_z.get::<Option<[bool; 8]>>(), None);
+    }
+
+    #[test]
+    fn bit_vectors_have_first_element_as_lsb() {
+        match [true, false, true, true].to_pin_value() {
+            PinValue::Bits { bits, width } => assert_eq!((bits, width), (0b1101, 4)),
+            v => panic!("expected bits, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn downcast_works_for_custom_and_builtin_values() {
+        let custom = PinValue::Custom(Box::new("text".to_string()));
+        assert_eq!(
+            custom.downcast_ref::<String>().map(String::as_str),
+            Some("text")
+        );
+        assert_eq!(custom.get::<u8>(), None);
+        assert_eq!(PinValue::U8(3).downcast_ref::<u8>(), Some(&3));
+        assert_eq!(PinValue::U8(3).downcast_ref::<String>(), None);
+    }
+}
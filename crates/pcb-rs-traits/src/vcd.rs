@@ -0,0 +1,417 @@
+use super::*;
+use std::any::{Any, TypeId};
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// This is implemented by custom pin data types which should be written to a VCD file.
+/// Pins of bool, integer, bit vector types and Option of those are written
+/// without needing this, see PinValue
+pub trait VcdValue: 'static {
+    /// number of bits used to represent the value
+    fn width() -> usize;
+
+    /// the value as string of bits (0,1,x or z), most significant bit first,
+    /// which must be exactly width() characters long
+    fn vcd_bits(&self) -> String;
+}
+
+impl<T: VcdValue> VcdValue for Option<T> {
+    fn width() -> usize {
+        T::width()
+    }
+
+    fn vcd_bits(&self) -> String {
+        match self {
+            Some(v) => v.vcd_bits(),
+            None => "z".repeat(T::width()),
+        }
+    }
+}
+
+// as the custom pin values are given to us as dyn Any, we keep a converter for each
+// registered type, which can downcast the value back to the type and convert it
+#[derive(Clone, Copy)]
+struct Converter {
+    width: usize,
+    to_bits: fn(&dyn Any) -> String,
+}
+
+impl Converter {
+    fn new<T: VcdValue>() -> Self {
+        Converter {
+            width: T::width(),
+            to_bits: |val| val.downcast_ref::<T>().unwrap().vcd_bits(),
+        }
+    }
+}
+
+struct Signal {
+    id: String,
+    last: String,
+}
+
+// a single sampled pin, scope is the path of chip names leading to the pin
+struct Sample {
+    scope: Vec<String>,
+    pin: &'static str,
+    width: usize,
+    bits: String,
+}
+
+#[derive(Debug)]
+/// Error given when sampling the pins of a chip for VCD
+pub enum VcdError {
+    /// writing to the underlying writer failed
+    Io(io::Error),
+    /// the pin has a custom data type which was neither registered nor skipped
+    UnregisteredType {
+        path: String,
+        data_type: &'static str,
+    },
+}
+
+impl From<io::Error> for VcdError {
+    fn from(e: io::Error) -> Self {
+        VcdError::Io(e)
+    }
+}
+
+impl std::fmt::Display for VcdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VcdError::Io(e) => write!(f, "cannot write the VCD : {}", e),
+            VcdError::UnregisteredType { path, data_type } => write!(
+                f,
+                "pin {} has type {}, which is not registered with the tracer, use register() or skip() for it",
+                path, data_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VcdError {}
+
+/// This writes the values of all pins of a chip, and recursively of its component
+/// chips in case of pcbs, as a Value Change Dump, which can be viewed in waveform
+/// viewers such as GTKWave. The tracer is not hooked into the ticks of the pcb, instead the
+/// pins are read on each call to sample(), and each call is one time unit, so usually it is
+/// called after each tick of the pcb. This means the values in between, such as at the rising
+/// edge of pcbs which also use the falling edge, are not recorded.
+/// Custom data types must either be registered using register(), or left out using skip()
+pub struct VcdTracer<W: Write> {
+    writer: W,
+    top_scope: String,
+    converters: HashMap<TypeId, Converter>,
+    skipped: HashSet<TypeId>,
+    signals: HashMap<String, Signal>,
+    started: bool,
+    time: u64,
+}
+
+fn get_identifier(mut index: usize) -> String {
+    // identifiers are made of printable ascii characters, from ! to ~
+    const FIRST: u8 = b'!';
+    const COUNT: usize = (b'~' - b'!' + 1) as usize;
+    let mut ret = String::new();
+    loop {
+        ret.push((FIRST + (index % COUNT) as u8) as char);
+        index /= COUNT;
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    ret
+}
+
+// signed values are sign extended when cast to u128, so we mask them to the width
+// to get their two's complement representation
+fn get_bits(val: u128, width: usize) -> String {
+    let mask = if width == 128 {
+        u128::MAX
+    } else {
+        (1 << width) - 1
+    };
+    format!("{:0width$b}", val & mask, width = width)
+}
+
+fn format_value(width: usize, bits: &str, id: &str) -> String {
+    if width == 1 {
+        format!("{}{}", bits, id)
+    } else {
+        format!("b{} {}", bits, id)
+    }
+}
+
+impl<W: Write> VcdTracer<W> {
+    /// Creates a new tracer writing to given writer, where all the pins will be under
+    /// a scope of the given name
+    pub fn new(writer: W, top_scope: &str) -> Self {
+        VcdTracer {
+            writer,
+            top_scope: top_scope.to_string(),
+            converters: HashMap::new(),
+            skipped: HashSet::new(),
+            signals: HashMap::new(),
+            started: false,
+            time: 0,
+        }
+    }
+
+    /// Registers a custom pin data type, so pins of that type will be traced as well.
+    /// Note that Option of a registered type must be registered separately.
+    /// This must be done before the first call to sample()
+    pub fn register<T: VcdValue>(&mut self) {
+        self.converters
+            .insert(TypeId::of::<T>(), Converter::new::<T>());
+    }
+
+    /// Leaves the pins of the given custom data type out of the trace, for types which cannot
+    /// implement VcdValue. Pins of custom types which are neither registered nor skipped
+    /// give an error when sampled
+    pub fn skip<T: 'static>(&mut self) {
+        self.skipped.insert(TypeId::of::<T>());
+    }
+
+    /// Records the current values of pins of the given chip and all its component chips.
+    /// The first call writes the VCD header declaring the signals, and the later calls only
+    /// write the values which have changed since the previous call
+    pub fn sample(&mut self, module: &dyn HardwareModule) -> Result<(), VcdError> {
+        let mut samples = Vec::new();
+        self.collect(module, &mut Vec::new(), &mut samples)?;
+
+        if !self.started {
+            self.started = true;
+            self.write_header(&samples)?;
+            writeln!(self.writer, "#0")?;
+            writeln!(self.writer, "$dumpvars")?;
+            for s in &samples {
+                let signal = self.signals.get(&Self::signal_key(s)).unwrap();
+                writeln!(
+                    self.writer,
+                    "{}",
+                    format_value(s.width, &s.bits, &signal.id)
+                )?;
+            }
+            writeln!(self.writer, "$end")?;
+        } else {
+            self.time += 1;
+            let mut changes = Vec::new();
+            for s in samples {
+                // signals which were not declared in the header cannot be added later
+                if let Some(signal) = self.signals.get_mut(&Self::signal_key(&s)) {
+                    if signal.last != s.bits {
+                        changes.push(format_value(s.width, &s.bits, &signal.id));
+                        signal.last = s.bits;
+                    }
+                }
+            }
+            if !changes.is_empty() {
+                writeln!(self.writer, "#{}", self.time)?;
+                for c in changes {
+                    writeln!(self.writer, "{}", c)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn signal_key(s: &Sample) -> String {
+        let mut key = s.scope.join(".");
+        key.push('.');
+        key.push_str(s.pin);
+        key
+    }
+
+    // we sort both the pins and the chips by name, as they are given in hashmaps,
+    // and we want the signal declarations to be in same order for every run
+    fn collect(
+        &self,
+        module: &dyn HardwareModule,
+        scope: &mut Vec<String>,
+        samples: &mut Vec<Sample>,
+    ) -> Result<(), VcdError> {
+        let mut pins: Vec<_> = module.get_pin_list().into_iter().collect();
+        pins.sort_unstable_by_key(|(pin, _)| *pin);
+        for (pin, md) in pins {
+            let val = match module.get_pin_value(pin) {
+                Some(v) => v,
+                None => continue,
+            };
+            match self.convert(&val) {
+                Some((width, bits)) => samples.push(Sample {
+                    scope: scope.clone(),
+                    pin,
+                    width,
+                    bits,
+                }),
+                None if self.skipped.contains(&md.type_id) => {}
+                None => {
+                    let mut path: Vec<&str> = scope.iter().map(String::as_str).collect();
+                    path.push(pin);
+                    return Err(VcdError::UnregisteredType {
+                        path: path.join("/"),
+                        data_type: md.data_type,
+                    });
+                }
+            }
+        }
+
+        let mut chips = module.get_chips();
+        chips.sort_unstable_by_key(|(name, _)| *name);
+        for (name, chip) in chips {
+            scope.push(name.to_string());
+            self.collect(chip, scope, samples)?;
+            scope.pop();
+        }
+        Ok(())
+    }
+
+    // gives None only for custom values of types which are not registered
+    fn convert(&self, val: &PinValue) -> Option<(usize, String)> {
+        let (val, width) = match val {
+            PinValue::Bool(v) => (*v as u128, 1),
+            PinValue::U8(v) => (*v as u128, 8),
+            PinValue::U16(v) => (*v as u128, 16),
+            PinValue::U32(v) => (*v as u128, 32),
+            PinValue::U64(v) => (*v as u128, 64),
+            PinValue::U128(v) => (*v, 128),
+            PinValue::Usize(v) => (*v as u128, usize::BITS as usize),
+            PinValue::I8(v) => (*v as u128, 8),
+            PinValue::I16(v) => (*v as u128, 16),
+            PinValue::I32(v) => (*v as u128, 32),
+            PinValue::I64(v) => (*v as u128, 64),
+            PinValue::I128(v) => (*v as u128, 128),
+            PinValue::Isize(v) => (*v as u128, isize::BITS as usize),
+            PinValue::Bits { bits, width } => (*bits, *width as usize),
+            PinValue::HighZ { width, .. } => {
+                return Some((*width as usize, "z".repeat(*width as usize)))
+            }
+            PinValue::Logic { bits, x, z, width } => {
+                let levels: String = (0..*width)
+                    .rev()
+                    .map(|i| logic::level_at(*bits, *x, *z, i).to_string())
+                    .collect();
+                return Some((*width as usize, levels));
+            }
+            PinValue::Custom(v) => {
+                let converter = self.converters.get(&v.as_ref().type_id())?;
+                return Some((converter.width, (converter.to_bits)(v.as_ref())));
+            }
+        };
+        Some((width, get_bits(val, width)))
+    }
+
+    fn write_header(&mut self, samples: &[Sample]) -> io::Result<()> {
+        writeln!(self.writer, "$timescale 1ns $end")?;
+        writeln!(self.writer, "$scope module {} $end", self.top_scope)?;
+        let mut current: &[String] = &[];
+        for (i, s) in samples.iter().enumerate() {
+            // close the scopes which this pin is not in, and open the ones it is in
+            let common = current
+                .iter()
+                .zip(s.scope.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            for _ in common..current.len() {
+                writeln!(self.writer, "$upscope $end")?;
+            }
+            for name in &s.scope[common..] {
+                writeln!(self.writer, "$scope module {} $end", name)?;
+            }
+            current = &s.scope;
+
+            let id = get_identifier(i);
+            writeln!(self.writer, "$var wire {} {} {} $end", s.width, id, s.pin)?;
+            self.signals.insert(
+                Self::signal_key(s),
+                Signal {
+                    id,
+                    last: s.bits.clone(),
+                },
+            );
+        }
+        for _ in 0..current.len() {
+            writeln!(self.writer, "$upscope $end")?;
+        }
+        writeln!(self.writer, "$upscope $end")?;
+        writeln!(self.writer, "$enddefinitions $end")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_unique_printable_strings() {
+        assert_eq!(get_identifier(0), "!");
+        assert_eq!(get_identifier(93), "~");
+        assert_eq!(get_identifier(94), "!!");
+        let ids: std::collections::HashSet<_> = (0..10000).map(get_identifier).collect();
+        assert_eq!(ids.len(), 10000);
+        assert!(ids
+            .iter()
+            .flat_map(|id| id.chars())
+            .all(|c| c.is_ascii_graphic()));
+    }
+
+    #[test]
+    fn signed_values_are_twos_complement() {
+        assert_eq!(get_bits(-1_i8 as u128, 8), "11111111");
+        assert_eq!(get_bits(5, 4), "0101");
+        assert_eq!(get_bits(u128::MAX, 128), "1".repeat(128));
+    }
+
+    #[test]
+    fn single_bit_values_have_no_prefix() {
+        assert_eq!(format_value(1, "1", "!"), "1!");
+        assert_eq!(format_value(4, "0101", "#"), "b0101 #");
+    }
+
+    struct Level(bool);
+
+    impl VcdValue for Level {
+        fn width() -> usize {
+            2
+        }
+
+        fn vcd_bits(&self) -> String {
+            if self.0 { "11" } else { "00" }.to_string()
+        }
+    }
+
+    #[test]
+    fn custom_values_need_registered_types() {
+        let mut tracer = VcdTracer::new(Vec::new(), "top");
+        let val = PinValue::Custom(Box::new(Level(true)));
+        assert!(tracer.convert(&val).is_none());
+        tracer.register::<Level>();
+        assert_eq!(tracer.convert(&val), Some((2, "11".to_string())));
+        tracer.register::<Option<Level>>();
+        let none = PinValue::Custom(Box::new(None::<Level>));
+        assert_eq!(tracer.convert(&none), Some((2, "zz".to_string())));
+    }
+
+    #[test]
+    fn tristated_and_logic_values_have_z_and_x() {
+        let tracer = VcdTracer::new(Vec::new(), "top");
+        let high_z = PinValue::HighZ {
+            kind: ValueKind::Bits,
+            width: 3,
+        };
+        assert_eq!(tracer.convert(&high_z), Some((3, "zzz".to_string())));
+        let logic = PinValue::Logic {
+            bits: 0b0001,
+            x: 0b0100,
+            z: 0b1000,
+            width: 4,
+        };
+        assert_eq!(tracer.convert(&logic), Some((4, "zx01".to_string())));
+    }
+}
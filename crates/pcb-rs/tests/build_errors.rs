@@ -0,0 +1,156 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Source {
+    #[pin(output)]
+    out: u8,
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Sink {
+    #[pin(input)]
+    a: u8,
+    #[pin(input)]
+    wide: u16,
+}
+
+impl Chip for Sink {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Faulty {
+    chip s;
+    chip k1;
+    chip k2;
+    chip k3;
+    chip s2;
+    chip gone;
+    s::out - k1::wide;
+    s::out - k2::a;
+    expose k1::a, k3::wide as x;
+    expose s2::out, k3::a as y;
+});
+
+pcb!(Pair {
+    chip s;
+    chip k;
+    s::out - k::a;
+});
+
+// when not complete, the chip gone is not added, and k2 is added as a chip without the pin a
+fn faulty(complete: bool) -> Result<Faulty, Vec<BuildError>> {
+    let k2: Box<PcbChip> = if complete {
+        Box::new(Sink::default())
+    } else {
+        Box::new(Source::default())
+    };
+    let mut builder = FaultyBuilder::new()
+        .add_chip("s", Box::new(Source::default()))
+        .add_chip("k1", Box::new(Sink::default()))
+        .add_chip("k2", k2)
+        .add_chip("k3", Box::new(Sink::default()))
+        .add_chip("s2", Box::new(Source::default()));
+    if complete {
+        builder = builder.add_chip("gone", Box::new(Source::default()));
+    }
+    builder.build()
+}
+
+fn pin(chip: &'static str, pin: &'static str) -> ChipPin {
+    ChipPin { chip, pin }
+}
+
+#[test]
+fn missing_chips_and_pins_are_collected() {
+    let errors = faulty(false).err().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(errors[0], BuildError::MissingChip("gone")));
+    assert!(matches!(errors[1], BuildError::UnknownPin(p) if p == pin("k2", "a")));
+}
+
+#[test]
+fn chips_added_under_undeclared_names_are_errors() {
+    let errors = PairBuilder::new()
+        .add_chip("s", Box::new(Source::default()))
+        .add_chip("k", Box::new(Sink::default()))
+        .add_chip("s3", Box::new(Source::default()))
+        .add_chip("extra", Box::new(Source::default()))
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::UndeclaredChip(c1), BuildError::UndeclaredChip(c2)] if c1 == "extra" && c2 == "s3"
+    ));
+}
+
+#[test]
+fn connection_errors_carry_pins_and_metadata() {
+    let errors = faulty(true).err().unwrap();
+    assert_eq!(errors.len(), 3);
+    assert!(errors.iter().any(|e| matches!(e,
+        BuildError::IncompatiblePins { first, second }
+            if first.0 == pin("s", "out")
+                && second.0 == pin("k1", "wide")
+                && second.1.data_type == "u16"
+    )));
+    assert!(errors.iter().any(|e| matches!(e,
+        BuildError::ExposedShortTypeMismatch { as_name: "x", pin: p, expected }
+            if p.0 == pin("k3", "wide") && expected.0 == pin("k1", "a")
+    )));
+    assert!(errors.iter().any(|e| matches!(e,
+        BuildError::ExposedShortNotInput { as_name: "y", pin: p }
+            if p.0 == pin("s2", "out") && matches!(p.1.pin_type, PinType::Output)
+    )));
+}
+
+#[test]
+fn errors_are_displayed() {
+    let error: Box<dyn std::error::Error> = Box::new(BuildError::MissingChip("gone"));
+    assert_eq!(
+        error.to_string(),
+        "chip gone defined in pcb design, but not added"
+    );
+}
+
+#[test]
+fn dynamic_pcb_checks_declaration() {
+    let errors = DynamicPcbBuilder::new()
+        .connect(pin("s", "out"), pin("s", "out"))
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(errors[0], BuildError::NoChips));
+    assert!(errors
+        .iter()
+        .any(|e| matches!(e, BuildError::SelfConnection(p) if *p == pin("s", "out"))));
+}
+
+#[test]
+fn group_with_multiple_drivers_is_error() {
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("s1", Box::new(Source::default()))
+        .add_chip("s2", Box::new(Source::default()))
+        .add_chip("k", Box::new(Sink::default()))
+        .connect(pin("s1", "out"), pin("k", "a"))
+        .connect(pin("s2", "out"), pin("k", "a"))
+        .build()
+        .err()
+        .unwrap();
+    match errors.as_slice() {
+        [BuildError::MultipleDrivers(pins)] => {
+            let mut pins: Vec<_> = pins.iter().map(|(p, _)| p.chip).collect();
+            pins.sort_unstable();
+            assert_eq!(pins, ["k", "s1", "s2"]);
+        }
+        e => panic!("expected multiple drivers error, got {:?}", e),
+    }
+}
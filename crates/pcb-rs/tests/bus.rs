@@ -0,0 +1,100 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Cpu {
+    #[pin(output)]
+    #[bus]
+    addr: [bool; 4],
+    #[pin(io, data_latch)]
+    #[bus]
+    data: [Option<bool>; 2],
+    data_latch: bool,
+}
+
+impl Chip for Cpu {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.addr = [true, false, true, true];
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Ram(
+    #[pin(input, name = "a")]
+    #[bus]
+    [bool; 4],
+    #[pin(input, name = "flag")] bool,
+);
+
+impl Chip for Ram {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip cpu: Cpu;
+    chip ram: Ram;
+    chip ram2: Ram;
+    cpu::addr[0..3] - ram::a[0..3];
+    cpu::addr[3] - ram::flag;
+    expose cpu::data[0..2] as data[0..2];
+    expose ram::a[3], ram2::a[3] as a3;
+    expose ram2::a[0..3] as low[0..3];
+});
+
+#[test]
+fn bus_elements_are_pins() {
+    let pins = Cpu::default().get_pin_list();
+    let mut names: Vec<_> = pins.keys().copied().collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        ["addr0", "addr1", "addr2", "addr3", "data0", "data1"]
+    );
+    assert_eq!(pins["addr2"].data_type, "bool");
+    assert!(pins["data1"].tristatable);
+    assert!(matches!(pins["data0"].pin_type, PinType::IO));
+
+    let mut cpu = Cpu::default();
+    cpu.set_pin_value("data1", &PinValue::Bool(true)).unwrap();
+    assert_eq!(cpu.data, [None, Some(true)]);
+    // io buses use the same latch for all their pins
+    cpu.data_latch = true;
+    assert!(cpu.in_input_mode("data0") && cpu.in_input_mode("data1"));
+}
+
+#[test]
+fn bus_ranges_connect_pins_one_to_one() {
+    let mut pcb = BoardBuilder::new()
+        .cpu(Cpu::default())
+        .ram(Ram::default())
+        .ram2(Ram::default())
+        .build()
+        .unwrap();
+    pcb.set_pin_value("low1", &PinValue::Bool(true)).unwrap();
+    pcb.tick();
+    assert_eq!(pcb.ram().0, [true, false, true, false]);
+    assert!(pcb.ram().1);
+    assert_eq!(pcb.ram2().0, [false, true, false, false]);
+
+    let mut exposed: Vec<_> = pcb.get_pin_list().into_keys().collect();
+    exposed.sort_unstable();
+    assert_eq!(exposed, ["a3", "data0", "data1", "low0", "low1", "low2"]);
+    pcb.set_pin_value("a3", &PinValue::Bool(true)).unwrap();
+    assert!(pcb.ram().0[3] && pcb.ram2().0[3]);
+}
+
+#[cfg(feature = "netlist")]
+#[test]
+fn netlist_buses_must_be_of_same_width() {
+    let json = r#"{
+        "chips": {"cpu": "cpu", "ram": "ram"},
+        "connections": ["cpu::addr[0..4] - ram::a[0..3]"]
+    }"#;
+    let registry = ChipRegistry::new()
+        .register_default::<Cpu>("cpu")
+        .register_default::<Ram>("ram");
+    let err = Netlist::from_json(json).unwrap().build(&registry).err();
+    assert!(matches!(err, Some(NetlistError::BusWidthMismatch { .. })));
+}
@@ -0,0 +1,128 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+#[combinational]
+struct FullAdder {
+    #[pin(input)]
+    a: bool,
+    #[pin(input)]
+    b: bool,
+    #[pin(input)]
+    cin: bool,
+    #[pin(output)]
+    s: bool,
+    #[pin(output)]
+    cout: bool,
+}
+
+impl Chip for FullAdder {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.s = self.a ^ self.b ^ self.cin;
+        self.cout = (self.a & self.b) | (self.cin & (self.a ^ self.b));
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Register {
+    #[pin(input)]
+    d: u8,
+    #[pin(output)]
+    q: u8,
+}
+
+impl Chip for Register {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.q = self.d;
+        Ok(())
+    }
+}
+
+pcb!(Adder8 {
+    chip fa[8];
+
+    for i in 0..7 {
+        fa[i]::cout - fa[i+1]::cin;
+    }
+    for i in 0..8 {
+        expose fa[i]::a as a[i];
+        expose fa[i]::b as b[i];
+        expose fa[i]::s as s[i];
+    }
+    expose fa[0]::cin as cin;
+    expose fa7::cout as cout;
+});
+
+// two chains of registers, of the even and of the odd registers
+pcb!(Interleaved {
+    chip regs[6]: Register priority 1;
+    chip last: Register;
+
+    for j in 0..=1 {
+        for i in 0..2 {
+            regs[2*i+j]::q - regs[2*(i+1)+j]::d;
+        }
+        expose regs[j]::d as input[j];
+    }
+    regs5::q - last::d;
+});
+
+fn add(pcb: &mut Adder8, a: u8, b: u8) -> (u8, bool) {
+    for i in 0..8 {
+        pcb.set_pin_value(&format!("a{}", i), &PinValue::Bool(a & (1 << i) != 0))
+            .unwrap();
+        pcb.set_pin_value(&format!("b{}", i), &PinValue::Bool(b & (1 << i) != 0))
+            .unwrap();
+    }
+    pcb.tick();
+    let sum = (0..8).fold(0, |acc, i| {
+        let bit = pcb
+            .get_pin_value(&format!("s{}", i))
+            .unwrap()
+            .get::<bool>()
+            .unwrap();
+        acc | ((bit as u8) << i)
+    });
+    (sum, pcb.get_pin_value("cout").unwrap().get().unwrap())
+}
+
+#[test]
+fn ripple_carry_adder_adds() {
+    let mut builder = Adder8Builder::new();
+    for i in 0..8 {
+        builder = builder.add_chip(&format!("fa{}", i), Box::new(FullAdder::default()));
+    }
+    let mut pcb = builder.build().unwrap();
+    assert_eq!(add(&mut pcb, 23, 42), (65, false));
+    assert_eq!(add(&mut pcb, 200, 100), (44, true));
+    assert_eq!(add(&mut pcb, 255, 1), (0, true));
+}
+
+#[test]
+fn typed_chip_arrays_have_methods_for_each_chip() {
+    let mut pcb = InterleavedBuilder::new()
+        .regs0(Register::default())
+        .regs1(Register::default())
+        .regs2(Register::default())
+        .regs3(Register::default())
+        .regs4(Register::default())
+        .regs5(Register::default())
+        .last(Register::default())
+        .build()
+        .unwrap();
+    pcb.set_pin_value("input0", &PinValue::U8(2)).unwrap();
+    pcb.set_pin_value("input1", &PinValue::U8(3)).unwrap();
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    assert_eq!(pcb.regs4().q, 2);
+    assert_eq!(pcb.regs5().q, 3);
+    assert_eq!(pcb.last().q, 0);
+    // the chips of the array have priority, so are ticked before last
+    let names: Vec<_> = pcb.get_chips().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(names.last(), Some(&"last"));
+    pcb.tick();
+    pcb.regs0_mut().q = 9;
+    assert_eq!(pcb.regs0().q, 9);
+    assert_eq!(pcb.last().q, 3);
+}
@@ -0,0 +1,115 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output)]
+    count: u8,
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+#[combinational]
+struct Not {
+    #[pin(input)]
+    a: bool,
+    #[pin(output)]
+    y: bool,
+}
+
+impl Chip for Not {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.y = !self.a;
+        Ok(())
+    }
+}
+
+pcb!(Clocked {
+    clock slow = main / 4;
+    clock slower = slow / 2 phase 1;
+    chip fast: Counter;
+    chip slow_counter: Counter @ slow;
+    chip slower_counter: Counter @ slower;
+});
+
+pcb!(ClockedGate {
+    clock slow = main / 2;
+    chip not: Not @ slow;
+    not::y - not::a;
+});
+
+fn clocked() -> Clocked {
+    ClockedBuilder::new()
+        .fast(Counter::default())
+        .slow_counter(Counter::default())
+        .slower_counter(Counter::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn chips_are_ticked_on_ticks_of_their_clocks() {
+    let mut pcb = clocked();
+    for _ in 0..9 {
+        pcb.tick();
+    }
+    assert_eq!(pcb.fast().count, 9);
+    // ticks 0, 4, 8
+    assert_eq!(pcb.slow_counter().count, 3);
+    // tick 4
+    assert_eq!(pcb.slower_counter().count, 1);
+}
+
+#[test]
+fn clocks_restart_on_reset() {
+    let mut pcb = clocked();
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    pcb.reset();
+    pcb.slow_counter_mut().count = 0;
+    pcb.tick();
+    assert_eq!(pcb.slow_counter().count, 1);
+}
+
+#[test]
+fn divided_clocks_have_phases() {
+    let slower = Clock::MAIN.divide(4, 0).divide(2, 1);
+    assert_eq!(
+        slower,
+        Clock {
+            divider: 8,
+            phase: 4
+        }
+    );
+    assert!(slower.ticks_at(12));
+    assert!(!slower.ticks_at(8));
+}
+
+#[test]
+fn combinational_chips_cannot_have_clocks() {
+    let errors = ClockedGateBuilder::new()
+        .not(Not::default())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::CombinationalClock("not")]
+    ));
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("not", Box::new(Not::default()))
+        .clock("not", Clock::MAIN.divide(2, 0))
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::CombinationalClock("not")]
+    ));
+}
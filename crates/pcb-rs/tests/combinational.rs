@@ -0,0 +1,116 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Source {
+    #[pin(output)]
+    out: bool,
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = !self.out;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+#[combinational]
+struct Not {
+    #[pin(input)]
+    a: bool,
+    #[pin(output)]
+    y: bool,
+}
+
+impl Chip for Not {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.y = !self.a;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Sink {
+    #[pin(input)]
+    a: bool,
+}
+
+impl Chip for Sink {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Chain {
+    chip source: Source;
+    chip n1: Not;
+    chip n2: Not;
+    chip n3: Not;
+    chip sink: Sink;
+    source::out - n1::a;
+    n1::y - n2::a;
+    n2::y - n3::a;
+    n3::y - sink::a;
+});
+
+pcb!(Oscillator {
+    chip n: Not;
+    n::y - n::a;
+});
+
+pcb!(Gates {
+    chip n1: Not;
+    chip n2: Not;
+    n1::y - n2::a;
+    expose n1::a as a;
+    expose n2::y as y;
+});
+
+#[test]
+fn values_pass_through_combinational_chips_in_one_tick() {
+    let mut pcb = ChainBuilder::new()
+        .source(Source::default())
+        .n1(Not::default())
+        .n2(Not::default())
+        .n3(Not::default())
+        .sink(Sink::default())
+        .build()
+        .unwrap();
+    for _ in 0..4 {
+        pcb.tick();
+        // three inverters, so the sink sees the inverse of the source in the same tick
+        assert_eq!(pcb.sink().a, !pcb.source().out);
+    }
+}
+
+#[test]
+fn oscillating_loop_gives_unsettled_error() {
+    let mut pcb = OscillatorBuilder::new().n(Not::default()).build().unwrap();
+    match pcb.try_tick() {
+        Err(SimError::Unsettled(chips)) => assert_eq!(chips, ["n"]),
+        r => panic!("expected unsettled error, got {:?}", r),
+    }
+}
+
+#[test]
+fn pcb_of_combinational_chips_is_combinational() {
+    let mut pcb = GatesBuilder::new()
+        .n1(Not::default())
+        .n2(Not::default())
+        .build()
+        .unwrap();
+    assert!(pcb.is_combinational());
+    pcb.set_pin_value("a", &PinValue::Bool(true)).unwrap();
+    pcb.tick();
+    assert_eq!(pcb.get_pin_value("y").unwrap().get::<bool>(), Some(true));
+    assert!(pcb.pin_value_eq("y", &PinValue::Bool(true)));
+}
+
+#[test]
+fn derived_combinational_chips_compare_outputs() {
+    let not = Not { a: false, y: true };
+    assert!(not.is_combinational());
+    assert!(not.pin_value_eq("y", &PinValue::Bool(true)));
+    assert!(!not.pin_value_eq("y", &PinValue::Bool(false)));
+    assert!(!Source::default().is_combinational());
+}
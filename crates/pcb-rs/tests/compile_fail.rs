@@ -0,0 +1,7 @@
+// the checks which pcb! and the Chip derive do when compiling, each file in ui is
+// a program which must fail to compile with the error in its .stderr file
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,308 @@
+use pcb_rs::*;
+
+// drives the bus when drive is Some, and is tristated otherwise
+#[derive(Chip, Default)]
+struct Driver {
+    #[pin(output)]
+    out: Option<u8>,
+    drive: Option<u8>,
+}
+
+impl Chip for Driver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = self.drive;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Reader {
+    #[pin(input)]
+    data: Option<u8>,
+}
+
+impl Chip for Reader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct TextDriver {
+    #[pin(output)]
+    out: Option<String>,
+}
+
+impl Chip for TextDriver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct TextReader {
+    #[pin(input)]
+    data: Option<String>,
+}
+
+impl Chip for TextReader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+// generic chips convert their values through PinData, when the type parameter implements it
+#[derive(Chip, Default)]
+struct GenericDriver<T> {
+    #[pin(output)]
+    out: Option<T>,
+}
+
+impl<T: Clone + CustomPinData> Chip for GenericDriver<T> {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+// manually implemented chip, which gives a custom value for its pin when custom is set
+#[derive(Default)]
+struct Manual {
+    custom: bool,
+}
+
+impl ChipInterface for Manual {
+    fn get_pin_list(&self) -> std::collections::HashMap<&'static str, PinMetadata> {
+        let md = PinMetadata {
+            pin_type: PinType::Output,
+            data_type: "Option<u8>",
+            type_id: std::any::TypeId::of::<Option<u8>>(),
+            tristatable: true,
+        };
+        std::collections::HashMap::from([("out", md)])
+    }
+
+    fn get_pin_value(&self, _name: &str) -> Option<PinValue> {
+        if self.custom {
+            Some(PinValue::Custom(Box::new(Some(1_u8))))
+        } else {
+            Some(PinValue::U8(1))
+        }
+    }
+
+    fn set_pin_value(&mut self, _name: &str, _val: &PinValue) -> Result<(), PinValueError> {
+        Ok(())
+    }
+
+    fn is_pin_tristated(&self, _name: &str) -> bool {
+        false
+    }
+
+    fn in_input_mode(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+impl Chip for Manual {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(WiredAndBus {
+    chip d1: Driver;
+    chip d2: Driver;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    contention reader::data = wired_and;
+});
+
+pcb!(WiredText {
+    chip d1: TextDriver;
+    chip d2: TextDriver;
+    chip reader: TextReader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    contention reader::data = wired_or;
+});
+
+pcb!(WiredGeneric {
+    chip d1: GenericDriver<u8>;
+    chip d2: GenericDriver<u8>;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    contention reader::data = wired_and;
+});
+
+pcb!(UnknownBus {
+    chip d1: Driver;
+    chip d2: Driver;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    contention reader::data = unknown;
+});
+
+pcb!(PanicBus {
+    chip d1: Driver;
+    chip d2: Driver;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+});
+
+pcb!(ErrorBus {
+    chip d1: Driver;
+    chip d2: Driver;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    contention reader::data = error;
+});
+
+fn driving(value: u8) -> Driver {
+    Driver {
+        out: None,
+        drive: Some(value),
+    }
+}
+
+#[test]
+fn contentions_of_last_tick_are_kept() {
+    let mut pcb = UnknownBusBuilder::new()
+        .d1(driving(1))
+        .d2(driving(2))
+        .reader(Reader::default())
+        .build()
+        .unwrap();
+    for _ in 0..5 {
+        pcb.try_tick().unwrap();
+    }
+    let contentions = pcb.take_contentions();
+    assert_eq!(contentions.len(), 1);
+    assert_eq!(contentions[0].policy, ContentionPolicy::Unknown);
+    assert_eq!(contentions[0].drivers.len(), 2);
+    assert!(pcb.take_contentions().is_empty());
+    // pins other than logic keep their values on contentions
+    assert_eq!(pcb.reader().data, None);
+}
+
+#[test]
+fn reset_clears_contentions() {
+    let mut pcb = UnknownBusBuilder::new()
+        .d1(driving(1))
+        .d2(driving(2))
+        .reader(Reader::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    pcb.reset();
+    assert!(pcb.take_contentions().is_empty());
+}
+
+#[test]
+fn error_policy_gives_contention_error() {
+    let mut pcb = ErrorBusBuilder::new()
+        .d1(driving(1))
+        .d2(driving(2))
+        .reader(Reader::default())
+        .build()
+        .unwrap();
+    assert!(matches!(pcb.try_tick(), Err(SimError::Contention { .. })));
+}
+
+fn panic_bus() -> PanicBus {
+    PanicBusBuilder::new()
+        .d1(driving(1))
+        .d2(driving(2))
+        .reader(Reader::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn panic_policy_gives_contention_error_from_try_tick() {
+    match panic_bus().try_tick() {
+        Err(SimError::Contention { contention, .. }) => {
+            assert_eq!(contention.policy, ContentionPolicy::Panic);
+            assert_eq!(contention.drivers.len(), 2);
+        }
+        r => panic!("expected contention error, got {:?}", r),
+    }
+}
+
+#[test]
+#[should_panic(expected = "Multiple pins found active at the same time")]
+fn panic_policy_panics_on_tick() {
+    panic_bus().tick();
+}
+
+#[test]
+fn wired_and_combines_active_values() {
+    let mut pcb = WiredAndBusBuilder::new()
+        .d1(Driver {
+            out: None,
+            drive: Some(0b1100),
+        })
+        .d2(Driver {
+            out: None,
+            drive: Some(0b1010),
+        })
+        .reader(Reader::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(0b1000));
+}
+
+#[test]
+fn wired_policy_on_non_bitwise_pins_fails_build() {
+    let errors = WiredTextBuilder::new()
+        .d1(TextDriver::default())
+        .d2(TextDriver::default())
+        .reader(TextReader::default())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::NotCombinable {
+            policy: ContentionPolicy::WiredOr,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn wired_policy_works_on_generic_chips() {
+    let mut pcb = WiredGenericBuilder::new()
+        .d1(GenericDriver { out: Some(0b1100) })
+        .d2(GenericDriver { out: Some(0b1010) })
+        .reader(Reader::default())
+        .build()
+        .unwrap();
+    pcb.try_tick().unwrap();
+    assert_eq!(pcb.reader().data, Some(0b1000));
+}
+
+#[test]
+fn uncombinable_values_give_error_when_ticking() {
+    let pin = |chip, pin| ChipPin { chip, pin };
+    let mut pcb = DynamicPcbBuilder::new()
+        .add_chip("d1", Box::new(Manual::default()))
+        .add_chip("d2", Box::new(Manual::default()))
+        .add_chip("reader", Box::new(Reader::default()))
+        .connect(pin("d1", "out"), pin("reader", "data"))
+        .connect(pin("d2", "out"), pin("reader", "data"))
+        .bus_contention_policy(pin("reader", "data"), ContentionPolicy::WiredAnd)
+        .build()
+        .unwrap();
+    pcb.try_tick().unwrap();
+    assert_eq!(pcb.get_chip::<Reader>("reader").unwrap().data, Some(1));
+    pcb.get_chip_mut::<Manual>("d2").unwrap().custom = true;
+    assert!(matches!(
+        pcb.try_tick(),
+        Err(SimError::NotCombinable { .. })
+    ));
+}
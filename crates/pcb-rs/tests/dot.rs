@@ -0,0 +1,82 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Source {
+    #[pin(output)]
+    out: u8,
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Buffer {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: Option<u8>,
+}
+
+impl Chip for Buffer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = Some(self.a);
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Reader {
+    #[pin(input)]
+    a: Option<u8>,
+}
+
+impl Chip for Reader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Inner {
+    chip r: Reader;
+    expose r::a as input;
+});
+
+pcb!(Outer {
+    chip inner: Inner;
+    chip src: Source;
+    chip b1: Buffer;
+    chip b2: Buffer;
+    src::out - b1::a;
+    b1::b - inner::input;
+    b2::b - inner::input;
+});
+
+#[test]
+fn dot_has_chips_pins_and_connections() {
+    let pcb = OuterBuilder::new()
+        .inner(InnerBuilder::new().r(Reader::default()).build().unwrap())
+        .src(Source::default())
+        .b1(Buffer::default())
+        .b2(Buffer::default())
+        .build()
+        .unwrap();
+    let dot = pcb.to_dot();
+    assert!(dot.starts_with("digraph pcb {"));
+    assert!(dot.contains(
+        "<TR><TD PORT=\"a\" BGCOLOR=\"lightblue\">a : u8</TD></TR>\
+         <TR><TD PORT=\"b\" BGCOLOR=\"lightsalmon\">b : core::option::Option&lt;u8&gt;</TD></TR>"
+    ));
+    // the nested pcb is a cluster, with its own pins as a node in it
+    let cluster = dot.split("subgraph \"cluster_inner\" {").nth(1).unwrap();
+    let cluster = &cluster[..cluster.find('}').unwrap()];
+    assert!(cluster.contains("\"inner\" [label="));
+    assert!(cluster.contains("\"inner/r\" [label="));
+    assert!(dot.contains("\"src\":\"out\" -> \"b1\":\"a\";"));
+    let bus: Vec<_> = dot.lines().filter(|l| l.contains("#bus")).collect();
+    assert_eq!(bus.len(), 4);
+    assert!(bus.contains(&"\"b2\":\"b\" -> \"#bus1\";"));
+    assert!(bus.contains(&"\"#bus1\" -> \"inner\":\"input\";"));
+}
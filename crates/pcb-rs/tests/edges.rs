@@ -0,0 +1,139 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct FlipFlop {
+    #[pin(input)]
+    d: u8,
+    #[pin(output)]
+    q: u8,
+    master: u8,
+}
+
+impl Chip for FlipFlop {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.master = self.d;
+        self.q = self.master;
+        Ok(())
+    }
+
+    fn try_tick_edge(&mut self, edge: ClockEdge) -> Result<(), SimError> {
+        match edge {
+            ClockEdge::Rising => self.master = self.d,
+            ClockEdge::Falling => self.q = self.master,
+        }
+        Ok(())
+    }
+
+    fn uses_falling_edge(&self) -> bool {
+        true
+    }
+}
+
+// counts its ticks, and the falling edges it was given, without using them
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output)]
+    count: u8,
+    falling: u8,
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn try_tick_edge(&mut self, edge: ClockEdge) -> Result<(), SimError> {
+        match edge {
+            ClockEdge::Rising => self.tick(),
+            ClockEdge::Falling => self.falling += 1,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Sink {
+    #[pin(input)]
+    a: u8,
+}
+
+impl Chip for Sink {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Clocked {
+    clock slow = main / 2;
+    chip counter: Counter;
+    chip ff: FlipFlop @ slow;
+    chip sink: Sink;
+    counter::count - ff::d;
+    ff::q - sink::a;
+});
+
+pcb!(RisingOnly {
+    chip counter: Counter;
+    chip sink: Sink;
+    counter::count - sink::a;
+});
+
+pcb!(Outer {
+    chip inner: Clocked;
+});
+
+fn clocked() -> Clocked {
+    ClockedBuilder::new()
+        .counter(Counter::default())
+        .ff(FlipFlop::default())
+        .sink(Sink::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn values_of_rising_edge_are_seen_on_falling_edge() {
+    let mut pcb = clocked();
+    assert!(pcb.uses_falling_edge());
+    pcb.try_tick_edge(ClockEdge::Rising).unwrap();
+    // the counter value is passed on after the rising edge, the flip flop took the old one
+    assert_eq!((pcb.ff().d, pcb.ff().master, pcb.ff().q), (1, 0, 0));
+    pcb.try_tick_edge(ClockEdge::Falling).unwrap();
+    assert_eq!(pcb.ff().q, 0);
+    // the slow clock does not tick the flip flop on cycle 1
+    pcb.tick();
+    assert_eq!((pcb.ff().master, pcb.ff().q), (0, 0));
+    pcb.tick();
+    assert_eq!((pcb.ff().master, pcb.ff().q), (2, 2));
+    assert_eq!(pcb.sink().a, 2);
+    assert_eq!(pcb.counter().count, 3);
+    // the other chips get the falling edges as well
+    assert_eq!(pcb.counter().falling, 3);
+}
+
+#[test]
+fn pcbs_without_falling_edge_chips_tick_once_per_cycle() {
+    let mut pcb = RisingOnlyBuilder::new()
+        .counter(Counter::default())
+        .sink(Sink::default())
+        .build()
+        .unwrap();
+    assert!(!pcb.uses_falling_edge());
+    pcb.tick();
+    pcb.tick();
+    assert_eq!(pcb.counter().count, 2);
+    assert_eq!(pcb.counter().falling, 0);
+    assert_eq!(pcb.sink().a, 2);
+}
+
+#[test]
+fn nested_pcbs_drive_falling_edge_of_their_chips() {
+    let mut pcb = OuterBuilder::new().inner(clocked()).build().unwrap();
+    assert!(pcb.uses_falling_edge());
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    assert_eq!(pcb.inner().ff().q, 2);
+    assert_eq!(pcb.inner().sink().a, 2);
+}
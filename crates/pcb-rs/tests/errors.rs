@@ -0,0 +1,72 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output)]
+    count: u8,
+}
+
+// implements only tick, so try_tick uses it
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Divider {
+    #[pin(input)]
+    divisor: u8,
+    #[pin(output)]
+    quotient: u8,
+}
+
+impl Chip for Divider {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        if self.divisor == 0 {
+            return Err(SimError::chip("division by zero"));
+        }
+        self.quotient = 100 / self.divisor;
+        Ok(())
+    }
+}
+
+pcb!(Divide {
+    chip counter: Counter;
+    chip divider: Divider;
+    counter::count - divider::divisor;
+});
+
+fn divide() -> Divide {
+    DivideBuilder::new()
+        .counter(Counter::default())
+        .divider(Divider::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn try_tick_defaults_to_tick() {
+    let mut counter = Counter::default();
+    counter.try_tick().unwrap();
+    assert_eq!(counter.count, 1);
+}
+
+#[test]
+fn pcb_gives_chip_errors_with_path() {
+    let mut pcb = divide();
+    match pcb.try_tick() {
+        Err(SimError::Chip { path, .. }) => assert_eq!(path, "divider"),
+        r => panic!("expected chip error, got {:?}", r),
+    }
+    pcb.divider_mut().divisor = 4;
+    pcb.try_tick().unwrap();
+    assert_eq!(pcb.divider().quotient, 25);
+}
+
+#[test]
+#[should_panic(expected = "division by zero")]
+fn pcb_tick_panics_on_chip_errors() {
+    divide().tick();
+}
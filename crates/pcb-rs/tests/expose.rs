@@ -0,0 +1,92 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Buffer {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Buffer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a;
+        Ok(())
+    }
+}
+
+pcb!(Exposing {
+    chip b1;
+    chip b2;
+    b1::b - b2::a;
+    expose b1::a as input;
+    expose b2::b as output;
+});
+
+fn pin(chip: &'static str, pin: &'static str) -> ChipPin {
+    ChipPin { chip, pin }
+}
+
+#[test]
+fn exposed_pins_are_pins_of_pcb() {
+    let mut pcb = ExposingBuilder::new()
+        .add_chip("b1", Box::new(Buffer::default()))
+        .add_chip("b2", Box::new(Buffer::default()))
+        .build()
+        .unwrap();
+    pcb.set_pin_value("input", &PinValue::U8(7)).unwrap();
+    pcb.tick();
+    pcb.tick();
+    assert_eq!(pcb.get_pin_value("output").unwrap().get::<u8>(), Some(7));
+    let pins = pcb.get_pin_list();
+    assert!(matches!(pins["input"].pin_type, PinType::Input));
+    assert!(matches!(pins["output"].pin_type, PinType::Output));
+}
+
+#[test]
+fn dynamic_pcb_rejects_expose_without_pins() {
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("b1", Box::new(Buffer::default()))
+        .expose(&[], "x")
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::ExposedNoPins("x")]
+    ));
+}
+
+#[test]
+fn dynamic_pcb_rejects_reused_exposed_name() {
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("b1", Box::new(Buffer::default()))
+        .add_chip("b2", Box::new(Buffer::default()))
+        .expose(&[pin("b1", "a")], "x")
+        .expose(&[pin("b2", "a")], "x")
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::ExposedNameReused("x")]
+    ));
+}
+
+#[test]
+fn dynamic_pcb_rejects_pin_exposed_twice() {
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("b1", Box::new(Buffer::default()))
+        .expose(&[pin("b1", "a")], "x")
+        .expose(&[pin("b1", "a")], "y")
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::ExposedMultipleTimes {
+            as_names: ("x", "y"),
+            ..
+        }]
+    ));
+}
@@ -0,0 +1,199 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+#[reset]
+struct Register<T> {
+    #[pin(input)]
+    d: T,
+    #[pin(output)]
+    q: T,
+    #[state]
+    last: T,
+}
+
+impl<T: Clone + CustomPinData> Chip for Register<T> {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.last = self.q.clone();
+        self.q = self.d.clone();
+        Ok(())
+    }
+}
+
+#[derive(Chip)]
+#[combinational]
+struct Mux<T, const N: usize>
+where
+    T: Copy,
+{
+    #[pin(input)]
+    inputs: [T; N],
+    #[pin(input)]
+    select: usize,
+    #[pin(output)]
+    out: Option<T>,
+}
+
+impl<T: Copy + PartialEq + CustomPinData, const N: usize> Chip for Mux<T, N> {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = self.inputs.get(self.select).copied();
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Buffer<T> {
+    #[pin(input)]
+    d: Option<T>,
+    #[pin(output)]
+    q: Option<T>,
+}
+
+impl<T: Clone + CustomPinData> Chip for Buffer<T> {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.q = self.d.clone();
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Tristated {
+    #[pin(output)]
+    out: Option<u8>,
+}
+
+impl Chip for Tristated {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Source {
+    #[pin(output)]
+    out: u8,
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out += 1;
+        Ok(())
+    }
+}
+
+pcb!(Pipeline {
+    chip source: Source;
+    chip r1: Register<u8>;
+    chip r2: Register<u8>;
+    source::out - r1::d;
+    r1::q - r2::d;
+});
+
+pcb!(Mismatched {
+    chip source;
+    chip wide;
+    source::out - wide::d;
+});
+
+#[test]
+fn pin_types_are_concrete_types() {
+    let pins = Register::<u16>::default().get_pin_list();
+    assert_eq!(pins["d"].data_type, "u16");
+    assert_eq!(pins["q"].type_id, std::any::TypeId::of::<u16>());
+    let mux = Mux {
+        inputs: [false; 4],
+        select: 0,
+        out: None,
+    };
+    let pins = mux.get_pin_list();
+    assert_eq!(pins["inputs"].type_id, std::any::TypeId::of::<[bool; 4]>());
+    assert!(pins["out"].tristatable);
+}
+
+#[test]
+fn generic_pins_are_passed_as_values_of_concrete_type() {
+    let mut reg = Register::<u8>::default();
+    reg.set_pin_value("d", &PinValue::U8(3)).unwrap();
+    assert!(reg.set_pin_value("d", &PinValue::U16(3)).is_err());
+    reg.tick();
+    assert!(matches!(reg.get_pin_value("q"), Some(PinValue::U8(3))));
+    // custom values of the type are accepted as well
+    reg.set_pin_value("d", &PinValue::Custom(Box::new(4_u8)))
+        .unwrap();
+    assert_eq!(reg.d, 4);
+
+    let bits = Register {
+        q: [true, false, false, true],
+        ..Default::default()
+    };
+    assert!(matches!(
+        bits.get_pin_value("q"),
+        Some(PinValue::Bits {
+            bits: 0b1001,
+            width: 4
+        })
+    ));
+
+    let mut mux = Mux::<u8, 2> {
+        inputs: [4, 5],
+        select: 1,
+        out: None,
+    };
+    mux.tick();
+    assert!(mux.pin_value_eq("out", &PinValue::U8(5)));
+    assert!(matches!(
+        mux.get_pin_value("inputs"),
+        Some(PinValue::Custom(_))
+    ));
+    mux.select = 2;
+    mux.tick();
+    assert!(matches!(
+        mux.get_pin_value("out"),
+        Some(PinValue::HighZ { .. })
+    ));
+}
+
+#[test]
+fn generic_pins_connect_to_concrete_tristated_pins() {
+    let pin = |chip, pin| ChipPin { chip, pin };
+    let mut pcb = DynamicPcbBuilder::new()
+        .add_chip("bus", Box::new(Tristated::default()))
+        .add_chip("g", Box::new(Buffer::<u8>::default()))
+        .connect(pin("bus", "out"), pin("g", "d"))
+        .build()
+        .unwrap();
+    pcb.try_tick().unwrap();
+    assert_eq!(pcb.get_chip::<Buffer<u8>>("g").unwrap().d, None);
+    pcb.get_chip_mut::<Tristated>("bus").unwrap().out = Some(7);
+    pcb.try_tick().unwrap();
+    pcb.try_tick().unwrap();
+    assert_eq!(pcb.get_chip::<Buffer<u8>>("g").unwrap().q, Some(7));
+}
+
+#[test]
+fn generic_chips_connect_to_chips_of_same_concrete_type() {
+    let mut pcb = PipelineBuilder::new()
+        .source(Source::default())
+        .r1(Register::default())
+        .r2(Register::default())
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    assert_eq!(pcb.r2().q, 1);
+    let state = pcb.save_state();
+    assert!(state.chips["r2"].state.contains_key("last"));
+    pcb.reset();
+    assert_eq!((pcb.r1().q, pcb.r1().last), (0, 0));
+
+    let errors = MismatchedBuilder::new()
+        .add_chip("source", Box::new(Source::default()))
+        .add_chip("wide", Box::new(Register::<u16>::default()))
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::IncompatiblePins { .. }]
+    ));
+}
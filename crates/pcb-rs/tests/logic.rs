@@ -0,0 +1,99 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Buffer {
+    #[pin(input)]
+    enable: Logic,
+    #[pin(input)]
+    a: LogicVec<4>,
+    #[pin(output)]
+    y: LogicVec<4>,
+}
+
+impl Chip for Buffer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.y = match self.enable {
+            Logic::One => self.a,
+            Logic::Zero => LogicVec::splat(Logic::Z),
+            _ => LogicVec::splat(Logic::X),
+        };
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Sink {
+    #[pin(input)]
+    d: LogicVec<4>,
+}
+
+impl Chip for Sink {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Bus {
+    chip b1: Buffer;
+    chip b2: Buffer;
+    chip sink: Sink;
+    b1::y - sink::d;
+    b2::y - sink::d;
+    expose b1::enable as en1;
+    expose b2::enable as en2;
+    expose b1::a as a1;
+    expose b2::a as a2;
+});
+
+fn bus() -> Bus {
+    BusBuilder::new()
+        .b1(Buffer::default())
+        .b2(Buffer::default())
+        .sink(Sink::default())
+        .build()
+        .unwrap()
+}
+
+fn drive(pcb: &mut Bus, en1: Logic, en2: Logic) -> LogicVec<4> {
+    pcb.set_pin_value("en1", &en1.to_pin_value()).unwrap();
+    pcb.set_pin_value("en2", &en2.to_pin_value()).unwrap();
+    pcb.tick();
+    pcb.sink().d
+}
+
+#[test]
+fn logic_pins_are_tristatable() {
+    let pins = Buffer::default().get_pin_list();
+    assert!(pins["enable"].tristatable && pins["y"].tristatable);
+    let mut buffer = Buffer::default();
+    assert!(!buffer.is_pin_tristated("y"));
+    buffer.enable = Logic::Zero;
+    buffer.tick();
+    assert!(buffer.is_pin_tristated("y"));
+}
+
+#[test]
+fn multiple_drivers_are_resolved() {
+    let mut pcb = bus();
+    pcb.set_pin_value("a1", &LogicVec::<4>::from_u128(0b0011).to_pin_value())
+        .unwrap();
+    pcb.set_pin_value("a2", &LogicVec::<4>::from_u128(0b0101).to_pin_value())
+        .unwrap();
+
+    assert_eq!(
+        drive(&mut pcb, Logic::One, Logic::Zero).to_u128(),
+        Some(0b0011)
+    );
+    assert_eq!(
+        drive(&mut pcb, Logic::Zero, Logic::One).to_u128(),
+        Some(0b0101)
+    );
+    // bits driven to different levels are unknown, and no contention is recorded
+    assert_eq!(drive(&mut pcb, Logic::One, Logic::One).to_string(), "0xx1");
+    assert!(pcb.take_contentions().is_empty());
+    assert!(drive(&mut pcb, Logic::Zero, Logic::Zero).is_z());
+    assert_eq!(
+        drive(&mut pcb, Logic::X, Logic::Zero),
+        LogicVec::splat(Logic::X)
+    );
+}
@@ -0,0 +1,94 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Driver {
+    #[pin(input)]
+    enable: bool,
+    #[pin(output)]
+    out: Option<u8>,
+}
+
+impl Chip for Driver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = if self.enable { Some(3) } else { None };
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Reader {
+    #[pin(input)]
+    data: Option<u8>,
+}
+
+impl Chip for Reader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+// the pull is stored in the pcb, so this checks that pcbs with pulls
+// can be used as chips of other pcbs, including with the `parallel` feature
+pcb!(Inner {
+    chip d1: Driver;
+    chip d2: Driver;
+    chip reader: Reader;
+    d1::out - reader::data;
+    d2::out - reader::data;
+    pull reader::data = 0xF0_u8;
+    expose d1::enable as enable;
+});
+
+#[derive(Chip, Default)]
+struct Enabler {
+    #[pin(output)]
+    enable: bool,
+}
+
+impl Chip for Enabler {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Outer {
+    chip enabler: Enabler;
+    chip inner: Inner;
+    enabler::enable - inner::enable;
+});
+
+fn inner() -> Inner {
+    InnerBuilder::new()
+        .d1(Driver::default())
+        .d2(Driver::default())
+        .reader(Reader::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn nested_pcb_ticks_its_chips() {
+    let mut pcb = OuterBuilder::new()
+        .enabler(Enabler::default())
+        .inner(inner())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.inner().reader().data, Some(0xF0));
+    pcb.enabler_mut().enable = true;
+    // one tick to pass enable to the driver, and one more to pass its output to the reader
+    pcb.tick();
+    pcb.tick();
+    assert_eq!(pcb.inner().reader().data, Some(3));
+}
+
+// chips, and the pin values stored in pcbs, are only required to be Send with the `parallel` feature
+#[cfg(feature = "parallel")]
+#[test]
+fn nested_pcb_is_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<PinValue>();
+    assert_send::<Pull>();
+    assert_send::<Inner>();
+    assert_send::<Outer>();
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "netlist")]
+
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Buffer {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Buffer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a;
+        Ok(())
+    }
+}
+
+const BOARD: &str = r#"
+connections = ["zeta::b - alpha::a"]
+expose = ["zeta::a as input", "alpha::b as output"]
+
+[chips]
+zeta = "buffer"
+alpha = "buffer"
+"#;
+
+fn registry() -> ChipRegistry {
+    ChipRegistry::new().register_default::<Buffer>("buffer")
+}
+
+fn source_chip(pcb: &DynamicPcb) -> &'static str {
+    match &pcb.get_pin_connections()[0] {
+        ConnectedPins::Pair { source, .. } => source.chip,
+        c => panic!("expected a pair connection, got {:?}", c),
+    }
+}
+
+#[test]
+fn chips_are_ticked_in_file_order() {
+    let pcb = Netlist::from_toml(BOARD)
+        .unwrap()
+        .build(&registry())
+        .unwrap();
+    let names: Vec<_> = pcb.get_chips().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["zeta", "alpha"]);
+    let json = r#"{"chips": {"zeta": "buffer", "alpha": "buffer"}}"#;
+    let pcb = Netlist::from_json(json)
+        .unwrap()
+        .build(&registry())
+        .unwrap();
+    let names: Vec<_> = pcb.get_chips().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["zeta", "alpha"]);
+}
+
+#[test]
+fn netlist_pcb_passes_values() {
+    let mut pcb = Netlist::from_toml(BOARD)
+        .unwrap()
+        .build(&registry())
+        .unwrap();
+    pcb.set_pin_value("input", &PinValue::U8(3)).unwrap();
+    pcb.tick();
+    pcb.tick();
+    assert_eq!(pcb.get_pin_value("output").unwrap().get::<u8>(), Some(3));
+}
+
+#[test]
+fn names_are_leaked_once() {
+    let netlist = Netlist::from_toml(BOARD).unwrap();
+    let first = netlist.build(&registry()).unwrap();
+    let second = netlist.build(&registry()).unwrap();
+    assert!(std::ptr::eq(source_chip(&first), source_chip(&second)));
+}
+
+#[test]
+fn unknown_chip_type_is_error() {
+    let json = r#"{"chips": {"c1": "missing"}}"#;
+    let err = Netlist::from_json(json).unwrap().build(&registry()).err();
+    assert!(matches!(err, Some(NetlistError::UnknownChipType { .. })));
+}
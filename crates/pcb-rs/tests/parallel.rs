@@ -0,0 +1,133 @@
+#![cfg(feature = "parallel")]
+
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Stage {
+    #[pin(input)]
+    d: u32,
+    #[pin(output)]
+    q: u32,
+}
+
+impl Chip for Stage {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.q = self.d + 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Failing {
+    #[pin(input)]
+    d: u32,
+}
+
+impl Chip for Failing {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        if self.d > 2 {
+            return Err(SimError::chip("value too large"));
+        }
+        Ok(())
+    }
+}
+
+const STAGES: usize = 200;
+
+fn names() -> Vec<&'static str> {
+    (0..STAGES)
+        .map(|i| &*Box::leak(format!("s{}", i).into_boxed_str()))
+        .collect()
+}
+
+// a pipeline of stages, where each stage adds 1 to the value of the previous one
+fn pipeline(names: &[&'static str]) -> DynamicPcbBuilder {
+    let mut builder = DynamicPcbBuilder::new();
+    for name in names {
+        builder = builder.add_chip(name, Box::new(Stage::default()));
+    }
+    for pair in names.windows(2) {
+        builder = builder.connect(
+            ChipPin {
+                chip: pair[0],
+                pin: "q",
+            },
+            ChipPin {
+                chip: pair[1],
+                pin: "d",
+            },
+        );
+    }
+    builder
+}
+
+// chips which are not Send can still be used on their own, they only cannot be added to pcbs
+#[derive(Chip, Default)]
+struct Local {
+    #[pin(input)]
+    d: u32,
+    seen: std::rc::Rc<std::cell::Cell<u32>>,
+}
+
+impl Chip for Local {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.seen.set(self.d);
+        Ok(())
+    }
+}
+
+#[test]
+fn chips_are_send() {
+    fn assert_send<T: Send>() {}
+    fn assert_hardware_module<T: HardwareModule>() {}
+    assert_send::<Box<PcbChip>>();
+    assert_send::<DynamicPcb>();
+    assert_hardware_module::<Local>();
+    let mut local = Local::default();
+    local.set_pin_value("d", &PinValue::U32(4)).unwrap();
+    local.tick();
+    assert_eq!(local.seen.get(), 4);
+}
+
+#[test]
+fn parallel_ticks_give_same_values_as_sequential() {
+    let names = names();
+    let mut pcb = pipeline(&names).build().unwrap();
+    for tick in 1..=STAGES {
+        pcb.tick();
+        // the value takes a tick to pass each stage, and each stage adds 1 to it
+        let last = pcb.get_chip::<Stage>(names[tick - 1]).unwrap();
+        assert_eq!(last.q, tick as u32);
+    }
+    let values: Vec<_> = names
+        .iter()
+        .map(|n| pcb.get_chip::<Stage>(n).unwrap().q)
+        .collect();
+    assert_eq!(values, (1..=STAGES as u32).collect::<Vec<_>>());
+}
+
+#[test]
+fn errors_of_chips_are_given() {
+    let names = names();
+    let mut pcb = pipeline(&names[..3])
+        .add_chip("failing", Box::new(Failing::default()))
+        .connect(
+            ChipPin {
+                chip: names[2],
+                pin: "q",
+            },
+            ChipPin {
+                chip: "failing",
+                pin: "d",
+            },
+        )
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        pcb.try_tick().unwrap();
+    }
+    match pcb.try_tick() {
+        Err(SimError::Chip { path, .. }) => assert_eq!(path, "failing"),
+        r => panic!("expected chip error, got {:?}", r),
+    }
+}
@@ -0,0 +1,152 @@
+use pcb_rs::*;
+use std::collections::HashMap;
+
+#[derive(Chip, Default)]
+struct Buffer {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: Option<u8>,
+    #[pin(io, io_latch)]
+    d: bool,
+    io_latch: bool,
+}
+
+impl Chip for Buffer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = Some(self.a);
+        Ok(())
+    }
+}
+
+// manually implemented chip, which does not give pin indices
+#[derive(Default)]
+struct Source {
+    value: u8,
+}
+
+impl ChipInterface for Source {
+    fn get_pin_list(&self) -> HashMap<&'static str, PinMetadata> {
+        let md = PinMetadata {
+            pin_type: PinType::Output,
+            data_type: "u8",
+            type_id: std::any::TypeId::of::<u8>(),
+            tristatable: false,
+        };
+        HashMap::from([("out", md)])
+    }
+
+    fn get_pin_value(&self, _name: &str) -> Option<PinValue> {
+        Some(PinValue::U8(self.value))
+    }
+
+    fn set_pin_value(&mut self, _name: &str, _val: &PinValue) -> Result<(), PinValueError> {
+        Ok(())
+    }
+
+    fn is_pin_tristated(&self, _name: &str) -> bool {
+        false
+    }
+
+    fn in_input_mode(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.value += 1;
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip source;
+    chip buffer: Buffer;
+    source::out - buffer::a;
+    expose buffer::b as b;
+    expose buffer::a as a;
+});
+
+#[test]
+fn derived_chips_give_pin_indices() {
+    let mut buffer = Buffer::default();
+    let indices: Vec<_> = ["a", "b", "d"]
+        .iter()
+        .map(|p| buffer.get_pin_index(p).unwrap())
+        .collect();
+    assert!(indices[0] != indices[1] && indices[1] != indices[2] && indices[0] != indices[2]);
+    assert_eq!(buffer.get_pin_index("c"), None);
+
+    let (a, b, d) = (indices[0], indices[1], indices[2]);
+    buffer.set_pin_value_at(a, &PinValue::U8(4)).unwrap();
+    assert_eq!(buffer.a, 4);
+    assert!(buffer.is_pin_tristated_at(b));
+    buffer.tick();
+    assert!(!buffer.is_pin_tristated_at(b));
+    assert_eq!(
+        buffer.get_pin_value_at(b).unwrap().get::<Option<u8>>(),
+        Some(Some(4))
+    );
+    buffer.io_latch = true;
+    assert!(buffer.in_input_mode_at(d));
+    assert!(!buffer.in_input_mode_at(a));
+}
+
+#[test]
+fn invalid_indices_and_values_are_errors() {
+    let mut buffer = Buffer::default();
+    let a = buffer.get_pin_index("a").unwrap();
+    assert!(matches!(
+        buffer.set_pin_value_at(a, &PinValue::Bool(true)),
+        Err(PinValueError::TypeMismatch { pin: "a", .. })
+    ));
+    assert!(matches!(
+        buffer.set_pin_value_at(10, &PinValue::U8(1)),
+        Err(PinValueError::UnknownPin(_))
+    ));
+    assert!(buffer.get_pin_value_at(10).is_none());
+}
+
+#[test]
+fn chips_without_indices_are_accessed_by_name() {
+    let chips: Vec<Box<PcbChip>> = vec![Box::new(Source::default()), Box::new(Buffer::default())];
+    let names = ["source".to_string(), "buffer".to_string()];
+    let out = resolve_pin(
+        &names,
+        &chips,
+        ChipPin {
+            chip: "source",
+            pin: "out",
+        },
+    );
+    assert_eq!(out.chip, 0);
+    assert_eq!(out.pin, PinIndex::Name("out"));
+    let a = resolve_pin(
+        &names,
+        &chips,
+        ChipPin {
+            chip: "buffer",
+            pin: "a",
+        },
+    );
+    assert_eq!(a.chip, 1);
+    assert_eq!(a.pin, PinIndex::Index(chips[1].get_pin_index("a").unwrap()));
+}
+
+#[test]
+fn pcb_passes_values_by_indices_and_names() {
+    let mut pcb = BoardBuilder::new()
+        .add_chip("source", Box::new(Source::default()))
+        .buffer(Buffer::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    pcb.tick();
+    let b = pcb.get_pin_index("b").unwrap();
+    assert_eq!(
+        pcb.get_pin_value_at(b).unwrap().get::<Option<u8>>(),
+        Some(Some(1))
+    );
+    assert_eq!(pcb.get_pin_value("a").unwrap().get::<u8>(), Some(2));
+}
@@ -0,0 +1,93 @@
+use pcb_rs::*;
+use std::any::TypeId;
+
+mod a {
+    #[derive(Debug, Clone, Default)]
+    pub struct Word;
+}
+
+mod b {
+    #[derive(Debug, Clone, Default)]
+    pub struct Word;
+}
+
+#[derive(Chip, Default)]
+struct Writer {
+    #[pin(output)]
+    out: Option<u8>,
+    #[pin(output)]
+    word: a::Word,
+}
+
+impl Chip for Writer {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = Some(5);
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Reader {
+    #[pin(input)]
+    data: std::option::Option<u8>,
+    #[pin(input)]
+    word: b::Word,
+}
+
+impl Chip for Reader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(SamePath {
+    chip w: Writer;
+    chip r: Reader;
+    w::out - r::data;
+});
+
+pcb!(SameName {
+    chip w: Writer;
+    chip r: Reader;
+    w::word - r::word;
+});
+
+#[test]
+fn metadata_has_type_id_and_name() {
+    let pins = Reader::default().get_pin_list();
+    assert_eq!(pins["data"].type_id, TypeId::of::<Option<u8>>());
+    assert_eq!(pins["data"].data_type, std::any::type_name::<Option<u8>>());
+    assert_ne!(
+        pins["word"].type_id,
+        Writer::default().get_pin_list()["word"].type_id
+    );
+}
+
+#[test]
+fn differently_written_same_types_connect() {
+    let mut pcb = SamePathBuilder::new()
+        .w(Writer::default())
+        .r(Reader::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.r().data, Some(5));
+}
+
+#[test]
+fn same_named_types_of_other_modules_do_not_connect() {
+    let errors = SameNameBuilder::new()
+        .w(Writer::default())
+        .r(Reader::default())
+        .build()
+        .err()
+        .unwrap();
+    match errors.as_slice() {
+        [BuildError::IncompatiblePins { first, second }] => {
+            let mut types = [first.1.data_type, second.1.data_type];
+            types.sort_unstable();
+            assert_eq!(types, ["pin_types::a::Word", "pin_types::b::Word"]);
+        }
+        e => panic!("expected incompatible pins error, got {:?}", e),
+    }
+}
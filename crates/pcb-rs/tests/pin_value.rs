@@ -0,0 +1,140 @@
+use pcb_rs::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Op(u8);
+
+#[derive(Chip, Default)]
+struct Unit {
+    #[pin(input)]
+    a: u8,
+    #[pin(input)]
+    op: Op,
+    #[pin(output)]
+    out: Option<u8>,
+}
+
+impl Chip for Unit {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = self.a.checked_add(self.op.0);
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip u1: Unit;
+    chip u2: Unit;
+    expose u1::a as a;
+    expose u1::op as op;
+    expose u2::op as op2;
+    expose u1::out as out;
+});
+
+#[test]
+fn type_mismatch_is_error() {
+    let mut unit = Unit::default();
+    assert_eq!(
+        unit.set_pin_value("a", &PinValue::U16(1)),
+        Err(PinValueError::TypeMismatch {
+            pin: "a",
+            expected: "u8"
+        })
+    );
+    assert!(matches!(
+        unit.set_pin_value("op", &PinValue::U8(1)),
+        Err(PinValueError::TypeMismatch { pin: "op", .. })
+    ));
+    assert_eq!(
+        unit.set_pin_value("b", &PinValue::U8(1)),
+        Err(PinValueError::UnknownPin("b".to_string()))
+    );
+    assert_eq!(unit.a, 0);
+}
+
+#[test]
+fn custom_types_are_boxed() {
+    let mut unit = Unit::default();
+    unit.set_pin_value("op", &PinValue::Custom(Box::new(Op(2))))
+        .unwrap();
+    unit.set_pin_value("a", &PinValue::U8(255)).unwrap();
+    assert_eq!(
+        unit.get_pin_value("op").unwrap().downcast_ref::<Op>(),
+        Some(&Op(2))
+    );
+    unit.tick();
+    // None is tristated
+    assert!(matches!(
+        unit.get_pin_value("out"),
+        Some(PinValue::HighZ {
+            kind: ValueKind::U8,
+            width: 8
+        })
+    ));
+}
+
+#[test]
+fn pcb_passes_values_to_chips() {
+    let mut pcb = BoardBuilder::new()
+        .u1(Unit::default())
+        .u2(Unit::default())
+        .build()
+        .unwrap();
+    pcb.set_pin_value("a", &PinValue::U8(1)).unwrap();
+    pcb.set_pin_value("op", &PinValue::Custom(Box::new(Op(2))))
+        .unwrap();
+    assert!(pcb.set_pin_value("op2", &PinValue::U8(2)).is_err());
+    pcb.tick();
+    assert_eq!(
+        pcb.get_pin_value("out").unwrap().get::<Option<u8>>(),
+        Some(Some(3))
+    );
+    assert_eq!(pcb.u2().op, Op(0));
+}
+
+// custom values are only required to be Send with the `parallel` feature
+#[cfg(not(feature = "parallel"))]
+mod not_send {
+    use pcb_rs::*;
+    use std::rc::Rc;
+
+    #[derive(Chip, Default)]
+    struct Tagger {
+        #[pin(output)]
+        tag: Rc<String>,
+    }
+
+    impl Chip for Tagger {
+        fn try_tick(&mut self) -> Result<(), SimError> {
+            self.tag = Rc::new("tagged".to_string());
+            Ok(())
+        }
+    }
+
+    #[derive(Chip, Default)]
+    struct TagReader {
+        #[pin(input)]
+        tag: Rc<String>,
+    }
+
+    impl Chip for TagReader {
+        fn try_tick(&mut self) -> Result<(), SimError> {
+            Ok(())
+        }
+    }
+
+    pcb!(Tags {
+        chip tagger: Tagger;
+        chip reader: TagReader;
+        tagger::tag - reader::tag;
+    });
+
+    #[test]
+    fn custom_types_need_not_be_send() {
+        let mut pcb = TagsBuilder::new()
+            .tagger(Tagger::default())
+            .reader(TagReader::default())
+            .build()
+            .unwrap();
+        pcb.tick();
+        assert_eq!(pcb.reader().tag.as_str(), "tagged");
+    }
+}
@@ -0,0 +1,240 @@
+use pcb_rs::*;
+
+// drives the bus when drive is Some, and is tristated otherwise
+#[derive(Chip, Default)]
+struct Driver {
+    #[pin(output)]
+    out: Option<u8>,
+    drive: Option<u8>,
+}
+
+impl Chip for Driver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.out = self.drive;
+        Ok(())
+    }
+}
+
+// io pin which stays in input mode
+#[derive(Chip, Default)]
+struct Reader {
+    #[pin(io, reading)]
+    data: Option<u8>,
+    reading: bool,
+}
+
+impl Chip for Reader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.reading = true;
+        Ok(())
+    }
+}
+
+pcb!(PulledBus {
+    chip driver: Driver;
+    chip reader: Reader;
+    chip other: Driver;
+    driver::out - reader::data;
+    other::out - reader::data;
+    pull reader::data = 0xAA_u8;
+});
+
+pcb!(KeptBus {
+    chip driver: Driver;
+    chip reader: Reader;
+    chip other: Driver;
+    driver::out - reader::data;
+    other::out - reader::data;
+    keep reader::data;
+});
+
+#[derive(Chip, Default)]
+struct LogicDriver {
+    #[pin(output)]
+    out: Logic,
+}
+
+impl Chip for LogicDriver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct LogicReader {
+    #[pin(input)]
+    a: Logic,
+}
+
+impl Chip for LogicReader {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Plain {
+    #[pin(output)]
+    out: u8,
+}
+
+impl Chip for Plain {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(PulledUpLine {
+    chip d1: LogicDriver;
+    chip d2: LogicDriver;
+    chip reader: LogicReader;
+    d1::out - reader::a;
+    d2::out - reader::a;
+    pull reader::a = Logic::One;
+});
+
+pcb!(KeptLine {
+    chip d1: LogicDriver;
+    chip d2: LogicDriver;
+    chip reader: LogicReader;
+    d1::out - reader::a;
+    d2::out - reader::a;
+    keep reader::a;
+});
+
+fn reader() -> Reader {
+    Reader {
+        data: None,
+        reading: true,
+    }
+}
+
+#[test]
+fn undriven_pulled_option_bus_gives_pull_value() {
+    let mut pcb = PulledBusBuilder::new()
+        .driver(Driver::default())
+        .reader(reader())
+        .other(Driver::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(0xAA));
+}
+
+#[test]
+fn pull_value_is_given_after_driver_stops() {
+    let mut pcb = PulledBusBuilder::new()
+        .driver(Driver {
+            drive: Some(5),
+            ..Default::default()
+        })
+        .reader(reader())
+        .other(Driver::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(5));
+    pcb.driver_mut().drive = None;
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(0xAA));
+}
+
+#[test]
+fn keeper_gives_last_driven_value() {
+    let mut pcb = KeptBusBuilder::new()
+        .driver(Driver {
+            drive: Some(7),
+            ..Default::default()
+        })
+        .reader(reader())
+        .other(Driver::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(7));
+    pcb.driver_mut().drive = None;
+    // the reader forgetting the value still gets it back from the keeper
+    pcb.reader_mut().data = None;
+    pcb.tick();
+    assert_eq!(pcb.reader().data, Some(7));
+}
+
+#[test]
+fn keeper_forgets_value_on_reset() {
+    let mut pcb = KeptBusBuilder::new()
+        .driver(Driver {
+            drive: Some(7),
+            ..Default::default()
+        })
+        .reader(reader())
+        .other(Driver::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    pcb.reset();
+    pcb.driver_mut().drive = None;
+    pcb.reader_mut().data = None;
+    pcb.tick();
+    assert_eq!(pcb.reader().data, None);
+}
+
+#[test]
+fn pull_of_wrong_type_is_build_error() {
+    let errors = PulledBusBuilder::new()
+        .driver(Driver::default())
+        .reader(reader())
+        .other(Driver::default())
+        .pull(
+            ChipPin {
+                chip: "reader",
+                pin: "data",
+            },
+            Pull::value(1_u16),
+        )
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(errors[0], BuildError::PullTypeMismatch { .. }));
+}
+
+#[test]
+fn undriven_logic_pins_get_pull_or_kept_value_instead_of_z() {
+    let driver = |out| LogicDriver { out };
+    let mut pulled = PulledUpLineBuilder::new()
+        .d1(driver(Logic::Z))
+        .d2(driver(Logic::Z))
+        .reader(LogicReader::default())
+        .build()
+        .unwrap();
+    pulled.tick();
+    assert_eq!(pulled.reader().a, Logic::One);
+    pulled.d2_mut().out = Logic::Zero;
+    pulled.tick();
+    assert_eq!(pulled.reader().a, Logic::Zero);
+
+    let mut kept = KeptLineBuilder::new()
+        .d1(driver(Logic::Zero))
+        .d2(driver(Logic::Z))
+        .reader(LogicReader::default())
+        .build()
+        .unwrap();
+    kept.tick();
+    kept.d1_mut().out = Logic::Z;
+    kept.tick();
+    assert_eq!(kept.reader().a, Logic::Zero);
+}
+
+#[test]
+fn pull_of_non_tristated_pin_is_build_error() {
+    let pin = ChipPin {
+        chip: "plain",
+        pin: "out",
+    };
+    let errors = DynamicPcbBuilder::new()
+        .add_chip("plain", Box::new(Plain::default()))
+        .pull(pin, Pull::value(0_u8))
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(errors.as_slice(), [BuildError::NotTristated(p)] if *p == pin));
+}
@@ -0,0 +1,125 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+#[reset]
+struct Register {
+    #[pin(input)]
+    d: u8,
+    #[pin(output)]
+    #[reset = 0xFF]
+    q: u8,
+    #[reset(Some(0))]
+    last: Option<u8>,
+}
+
+impl Chip for Register {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.last = Some(self.q);
+        self.q = self.d;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output)]
+    count: u8,
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count += 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Debug, PartialEq)]
+#[reset]
+enum Mode {
+    Idle(#[pin(output, name = "busy")] bool),
+    Busy {
+        #[pin(output)]
+        busy: bool,
+    },
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Idle(false)
+    }
+}
+
+impl Chip for Mode {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        *self = Mode::Busy { busy: true };
+        Ok(())
+    }
+}
+
+pcb!(Inner {
+    chip reg: Register;
+    chip mode: Mode;
+    expose reg::d as d;
+    expose reg::q as q;
+});
+
+pcb!(Outer {
+    chip counter: Counter;
+    chip inner: Inner;
+    counter::count - inner::d;
+});
+
+#[test]
+fn fields_are_reset_to_given_values() {
+    let mut reg = Register {
+        d: 3,
+        q: 4,
+        last: None,
+    };
+    reg.reset();
+    assert_eq!((reg.d, reg.q, reg.last), (0, 0xFF, Some(0)));
+}
+
+#[test]
+fn enums_are_reset_to_default() {
+    let mut mode = Mode::default();
+    mode.tick();
+    assert_eq!(mode, Mode::Busy { busy: true });
+    mode.reset();
+    assert_eq!(mode, Mode::Idle(false));
+}
+
+#[test]
+fn pcb_reset_cascades_to_nested_chips() {
+    let inner = InnerBuilder::new()
+        .reg(Register::default())
+        .mode(Mode::default())
+        .build()
+        .unwrap();
+    let mut pcb = OuterBuilder::new()
+        .counter(Counter::default())
+        .inner(inner)
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    assert_eq!(pcb.inner().reg().q, 2);
+    pcb.reset();
+    assert_eq!(pcb.inner().reg().d, 0);
+    assert_eq!(pcb.inner().reg().q, 0xFF);
+    assert_eq!(*pcb.inner().mode(), Mode::Idle(false));
+    // chips without #[reset] keep their state
+    assert_eq!(pcb.counter().count, 3);
+}
+
+#[test]
+fn dynamic_pcb_resets_its_chips() {
+    let mut pcb = DynamicPcbBuilder::new()
+        .add_chip("reg", Box::new(Register::default()))
+        .build()
+        .unwrap();
+    pcb.get_chip_mut::<Register>("reg").unwrap().q = 1;
+    pcb.reset();
+    assert_eq!(pcb.get_chip::<Register>("reg").unwrap().q, 0xFF);
+}
@@ -0,0 +1,106 @@
+use pcb_rs::*;
+
+// the output pin is named q, and field 2 is state
+#[derive(Chip, Default)]
+struct Latch(
+    #[pin(input)] u8,
+    #[pin(output, name = "q")] u8,
+    #[state] usize,
+);
+
+impl Chip for Latch {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.1 = self.0;
+        self.2 += 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output, name = "out")]
+    count: u8,
+    #[state]
+    #[pin(input)]
+    step: u8,
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count = self.count.wrapping_add(self.step);
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    clock slow = main / 2;
+    chip counter: Counter;
+    chip latch: Latch @ slow;
+    counter::out - latch::0;
+});
+
+#[test]
+fn pins_are_saved_by_pin_names() {
+    let latch = Latch(1, 2, 3);
+    let state = latch.save_state();
+    let pins: Vec<_> = state.pins.keys().map(String::as_str).collect();
+    assert_eq!(pins, ["0", "q"]);
+    let fields: Vec<_> = state.state.keys().map(String::as_str).collect();
+    assert_eq!(fields, ["2"]);
+    // same as the default implementation, for the pins
+    assert_eq!(
+        state.pins["q"].get::<u8>(),
+        latch.get_pin_value("q").unwrap().get()
+    );
+
+    let mut restored = Latch::default();
+    restored.restore_state(&state).unwrap();
+    assert_eq!((restored.0, restored.1, restored.2), (1, 2, 3));
+}
+
+#[test]
+fn fields_which_are_pins_are_saved_once() {
+    let counter = Counter { count: 4, step: 2 };
+    let state = counter.save_state();
+    assert_eq!(state.pins.len(), 2);
+    assert!(state.pins.contains_key("out"));
+    assert!(state.state.is_empty());
+}
+
+#[test]
+fn unknown_state_field_is_error() {
+    let mut state = Latch::default().save_state();
+    state.state.insert("3".to_string(), PinValue::U8(0));
+    assert!(matches!(
+        Latch::default().restore_state(&state),
+        Err(StateError::InvalidValue { .. })
+    ));
+}
+
+#[test]
+fn pcb_state_restores_chips_and_cycle() {
+    let build = || {
+        BoardBuilder::new()
+            .counter(Counter { count: 0, step: 1 })
+            .latch(Latch::default())
+            .build()
+            .unwrap()
+    };
+    let mut pcb = build();
+    for _ in 0..3 {
+        pcb.tick();
+    }
+    let state = pcb.save_state();
+    assert_eq!(state.state["cycle"].get::<u64>(), Some(3));
+    assert_eq!(state.chips["latch"].state["2"].get::<usize>(), Some(2));
+
+    let mut restored = build();
+    restored.restore_state(&state).unwrap();
+    for p in [&mut pcb, &mut restored] {
+        p.tick();
+        p.tick();
+    }
+    assert_eq!(restored.latch().2, pcb.latch().2);
+    assert_eq!(restored.latch().1, pcb.latch().1);
+    assert_eq!(restored.counter().count, 5);
+}
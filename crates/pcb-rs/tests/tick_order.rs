@@ -0,0 +1,102 @@
+#![cfg(not(feature = "parallel"))]
+
+use pcb_rs::*;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+// records its name in the shared log when ticked
+#[derive(Chip)]
+struct Logger {
+    #[pin(output)]
+    out: bool,
+    name: &'static str,
+    log: Log,
+}
+
+impl Chip for Logger {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.log.lock().unwrap().push(self.name);
+        Ok(())
+    }
+}
+
+fn logger(name: &'static str, log: &Log) -> Box<Logger> {
+    Box::new(Logger {
+        out: false,
+        name,
+        log: log.clone(),
+    })
+}
+
+pcb!(Declared {
+    chip c;
+    chip a;
+    chip b;
+});
+
+pcb!(Prioritized {
+    chip low priority -1;
+    chip first;
+    chip high priority 2;
+    chip second;
+});
+
+fn ticked(
+    names: &[&'static str],
+    build: impl Fn(&Log) -> Box<dyn HardwareModule>,
+) -> Vec<&'static str> {
+    let log = Log::default();
+    let mut pcb = build(&log);
+    pcb.tick();
+    let order = log.lock().unwrap().clone();
+    assert_eq!(order.len(), names.len());
+    order
+}
+
+#[test]
+fn chips_are_ticked_in_declaration_order() {
+    let names = ["c", "a", "b"];
+    let build = |log: &Log| -> Box<dyn HardwareModule> {
+        // adding order does not matter
+        Box::new(
+            DeclaredBuilder::new()
+                .add_chip("a", logger("a", log))
+                .add_chip("b", logger("b", log))
+                .add_chip("c", logger("c", log))
+                .build()
+                .unwrap(),
+        )
+    };
+    for _ in 0..3 {
+        assert_eq!(ticked(&names, build), names);
+    }
+}
+
+#[test]
+fn chips_with_higher_priority_are_ticked_first() {
+    let names = ["low", "first", "high", "second"];
+    let build = |log: &Log| -> Box<dyn HardwareModule> {
+        let mut builder = PrioritizedBuilder::new();
+        for name in names {
+            builder = builder.add_chip(name, logger(name, log));
+        }
+        Box::new(builder.build().unwrap())
+    };
+    assert_eq!(ticked(&names, build), ["high", "first", "second", "low"]);
+}
+
+#[test]
+fn dynamic_pcb_ticks_in_adding_order_and_priority() {
+    let build = |log: &Log| -> Box<dyn HardwareModule> {
+        Box::new(
+            DynamicPcbBuilder::new()
+                .add_chip("b", logger("b", log))
+                .add_chip("a", logger("a", log))
+                .add_chip_with_priority("z", 1, logger("z", log))
+                .build()
+                .unwrap(),
+        )
+    };
+    assert_eq!(ticked(&["b", "a", "z"], build), ["z", "b", "a"]);
+}
@@ -0,0 +1,113 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inverter(#[pin(input)] bool, #[pin(output, name = "q")] bool);
+
+impl Chip for Inverter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.1 = !self.0;
+        Ok(())
+    }
+}
+
+// pins 0 and data, where field 2 is the latch of the data pin
+#[derive(Chip, Default)]
+struct Port(
+    #[pin(input)] bool,
+    #[pin(io, 2, name = "data")] Option<u8>,
+    bool,
+);
+
+impl Chip for Port {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.2 = self.0;
+        Ok(())
+    }
+}
+
+// the pins are in different fields and order in each variant
+#[derive(Chip, Debug, PartialEq)]
+enum Counter {
+    Counting {
+        #[pin(input)]
+        en: bool,
+        #[pin(output)]
+        q: u8,
+    },
+    Halted(
+        #[pin(output, name = "q")] u8,
+        #[pin(input, name = "en")] bool,
+    ),
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Counter::Counting { en: false, q: 0 }
+    }
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        *self = match *self {
+            Counter::Counting { en: true, q } if q < 2 => Counter::Counting { en: true, q: q + 1 },
+            Counter::Counting { en: true, q } => Counter::Halted(q, true),
+            Counter::Counting { en: false, q } => Counter::Counting { en: false, q },
+            Counter::Halted(q, en) => Counter::Halted(q, en),
+        };
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip inv: Inverter;
+    chip counter: Counter;
+    inv::q - counter::en;
+    expose counter::q as q;
+});
+
+#[test]
+fn tuple_fields_are_pins_by_index_or_name() {
+    let mut pins: Vec<_> = Inverter::default().get_pin_list().into_keys().collect();
+    pins.sort_unstable();
+    assert_eq!(pins, ["0", "q"]);
+
+    let mut port = Port::default();
+    port.set_pin_value("0", &PinValue::Bool(true)).unwrap();
+    assert!(!port.in_input_mode("data"));
+    port.tick();
+    assert!(port.in_input_mode("data"));
+    port.set_pin_value("data", &Some(3_u8).to_pin_value())
+        .unwrap();
+    assert_eq!(port.1, Some(3));
+    assert!(port.get_pin_list()["data"].tristatable);
+}
+
+#[test]
+fn enum_pins_are_of_current_variant() {
+    let mut counter = Counter::Halted(5, false);
+    assert_eq!(counter.get_pin_value("q").unwrap().get::<u8>(), Some(5));
+    counter.set_pin_value("en", &PinValue::Bool(true)).unwrap();
+    assert_eq!(counter, Counter::Halted(5, true));
+    let pins = counter.get_pin_list();
+    assert!(matches!(pins["q"].pin_type, PinType::Output));
+    assert!(matches!(pins["en"].pin_type, PinType::Input));
+    let state = counter.save_state();
+    assert!(state.state.is_empty());
+    let mut restored = Counter::default();
+    restored.restore_state(&state).unwrap();
+    assert_eq!(restored, Counter::Counting { en: true, q: 5 });
+}
+
+#[test]
+fn tuple_and_enum_chips_in_pcb() {
+    let mut pcb = BoardBuilder::new()
+        .inv(Inverter::default())
+        .counter(Counter::default())
+        .build()
+        .unwrap();
+    for _ in 0..5 {
+        pcb.tick();
+    }
+    assert_eq!(*pcb.counter(), Counter::Halted(2, true));
+    assert_eq!(pcb.get_pin_value("q").unwrap().get::<u8>(), Some(2));
+}
@@ -0,0 +1,170 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a.wrapping_add(1);
+        Ok(())
+    }
+}
+
+// manually implemented chip, which does not implement ChipPins
+#[derive(Default)]
+struct Source {
+    value: u8,
+}
+
+impl ChipInterface for Source {
+    fn get_pin_list(&self) -> std::collections::HashMap<&'static str, PinMetadata> {
+        let md = PinMetadata {
+            pin_type: PinType::Output,
+            data_type: "u8",
+            type_id: std::any::TypeId::of::<u8>(),
+            tristatable: false,
+        };
+        std::collections::HashMap::from([("out", md)])
+    }
+
+    fn get_pin_value(&self, _name: &str) -> Option<PinValue> {
+        Some(PinValue::U8(self.value))
+    }
+
+    fn set_pin_value(&mut self, _name: &str, _val: &PinValue) -> Result<(), PinValueError> {
+        Ok(())
+    }
+
+    fn is_pin_tristated(&self, _name: &str) -> bool {
+        false
+    }
+
+    fn in_input_mode(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+impl Chip for Source {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(ManualBoard {
+    chip source: Source;
+    chip inc: Inc;
+    source::out - inc::a;
+    expose inc::b as b;
+});
+
+pcb!(Mixed {
+    chip inc: Inc;
+    chip other;
+    inc::b - other::a;
+});
+
+// exposes a pin of a chip whose pins are not known, so its pins are not known either
+pcb!(ExposingManual {
+    chip source: Source;
+    chip inc: Inc;
+    source::out - inc::a;
+    expose source::out as out;
+});
+
+pcb!(Outer {
+    chip inner: ExposingManual;
+    chip dynamic: DynamicPcb;
+    chip inc: Inc;
+    inner::out - inc::a;
+    dynamic::out - inc::a;
+});
+
+#[test]
+fn manual_chips_can_be_typed() {
+    let mut pcb = ManualBoardBuilder::new()
+        .source(Source { value: 41 })
+        .inc(Inc::default())
+        .build()
+        .unwrap();
+    pcb.tick();
+    pcb.tick();
+    assert_eq!(pcb.inc().b, 42);
+    assert_eq!(pcb.source().value, 41);
+}
+
+#[test]
+fn pins_of_pcbs_are_known_only_for_chips_giving_pins() {
+    const { assert!(<ManualBoard as ChipPins>::KNOWN) };
+    const { assert!(!<ExposingManual as ChipPins>::KNOWN) };
+    let pins = <ManualBoard as ChipPins>::PINS;
+    assert_eq!(pins.len(), 1);
+    assert_eq!(pins[0].name, "b");
+    assert!(matches!(pins[0].pin_type, PinType::Output));
+}
+
+#[test]
+fn pins_of_unknown_chips_are_checked_when_building() {
+    let inner = ExposingManualBuilder::new()
+        .source(Source { value: 1 })
+        .inc(Inc::default())
+        .build()
+        .unwrap();
+    // the dynamic pcb does not expose out, so building fails instead of compiling
+    let dynamic = DynamicPcbBuilder::new()
+        .add_chip("inc", Box::new(Inc::default()))
+        .build()
+        .unwrap();
+    let errors = OuterBuilder::new()
+        .inner(inner)
+        .dynamic(dynamic)
+        .inc(Inc::default())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        errors.as_slice(),
+        [BuildError::UnknownPin(ChipPin {
+            chip: "dynamic",
+            pin: "out"
+        })]
+    ));
+}
+
+#[test]
+fn typed_chips_have_accessors() {
+    let mut pcb = MixedBuilder::new()
+        .inc(Inc::default())
+        .add_chip("other", Box::new(Inc::default()))
+        .build()
+        .unwrap();
+    pcb.inc_mut().a = 4;
+    pcb.tick();
+    assert_eq!(pcb.inc().b, 5);
+    // the untyped chips, and the typed ones, can still be taken by name
+    assert_eq!(pcb.get_chip::<Inc>("other").unwrap().a, 5);
+    assert_eq!(pcb.get_chip::<Inc>("inc").unwrap().b, 5);
+    assert!(pcb.get_chip::<Source>("inc").is_none());
+}
+
+#[test]
+fn typed_chip_replaced_by_name_is_type_checked() {
+    let errors = MixedBuilder::new()
+        .inc(Inc::default())
+        .add_chip("inc", Box::new(Source::default()))
+        .add_chip("other", Box::new(Inc::default()))
+        .build()
+        .err()
+        .unwrap();
+    match errors.as_slice() {
+        [BuildError::ChipTypeMismatch { chip, expected }, ..] => {
+            assert_eq!(*chip, "inc");
+            assert!(expected.ends_with("Inc"));
+        }
+        e => panic!("expected chip type mismatch, got {:?}", e),
+    }
+}
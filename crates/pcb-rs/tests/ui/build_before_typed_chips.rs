@@ -0,0 +1,27 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip i1: Inc;
+    chip i2: Inc;
+    i1::b - i2::a;
+});
+
+fn main() {
+    // i2 is not added, so the builder does not have build yet
+    let _ = BoardBuilder::new().i1(Inc::default()).build();
+}
@@ -0,0 +1,14 @@
+use pcb_rs::*;
+
+// the pin 0 of addr1 and the pin 10 of addr would both be named addr10
+#[derive(Chip, Default)]
+struct Cpu {
+    #[pin(output)]
+    #[bus]
+    addr: [bool; 11],
+    #[pin(output)]
+    #[bus]
+    addr1: [bool; 1],
+}
+
+fn main() {}
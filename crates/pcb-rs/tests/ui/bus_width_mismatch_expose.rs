@@ -0,0 +1,35 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Cpu {
+    #[pin(output)]
+    #[bus]
+    addr: [bool; 8],
+}
+
+impl Chip for Cpu {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Ram {
+    #[pin(input)]
+    #[bus]
+    a: [bool; 8],
+}
+
+impl Chip for Ram {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip cpu: Cpu;
+    chip ram: Ram;
+    expose ram::a[0..8], cpu::addr[0..4] as a[0..8];
+});
+
+fn main() {}
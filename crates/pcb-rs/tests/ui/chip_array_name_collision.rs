@@ -0,0 +1,9 @@
+use pcb_rs::*;
+
+// the chip 0 of reg1 and the chip 10 of reg would both be named reg10
+pcb!(Board {
+    chip reg[11];
+    chip reg1[1];
+});
+
+fn main() {}
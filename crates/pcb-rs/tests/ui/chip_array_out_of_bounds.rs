@@ -0,0 +1,25 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Chain {
+    chip inc[4]: Inc;
+    for i in 0..4 {
+        inc[i]::b - inc[i+1]::a;
+    }
+});
+
+fn main() {}
@@ -0,0 +1,24 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Chain {
+    chip inc: Inc;
+    chip other: Inc;
+    inc[0]::b - other::a;
+});
+
+fn main() {}
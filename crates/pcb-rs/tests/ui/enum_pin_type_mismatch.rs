@@ -0,0 +1,18 @@
+use pcb_rs::*;
+
+#[derive(Chip)]
+enum Counter {
+    Counting {
+        #[pin(output)]
+        q: u8,
+    },
+    Halted(#[pin(output, name = "q")] u16),
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+fn main() {}
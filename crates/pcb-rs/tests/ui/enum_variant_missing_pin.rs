@@ -0,0 +1,20 @@
+use pcb_rs::*;
+
+#[derive(Chip)]
+enum Counter {
+    Counting {
+        #[pin(input)]
+        en: bool,
+        #[pin(output)]
+        q: u8,
+    },
+    Halted(#[pin(output, name = "q")] u8),
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+fn main() {}
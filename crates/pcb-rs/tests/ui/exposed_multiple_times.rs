@@ -0,0 +1,24 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip i1: Inc;
+    expose i1::a as a;
+    expose i1::a as b;
+});
+
+fn main() {}
@@ -0,0 +1,10 @@
+use pcb_rs::*;
+
+pcb!(Board {
+    chip b1;
+    chip b2;
+    expose b1::a as x;
+    expose b2::a as x;
+});
+
+fn main() {}
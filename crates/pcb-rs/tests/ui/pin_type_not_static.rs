@@ -0,0 +1,16 @@
+use pcb_rs::*;
+
+// pins are compared by their TypeId, so the pin types must be 'static
+#[derive(Chip)]
+struct Reader<'a> {
+    #[pin(input)]
+    data: &'a u8,
+}
+
+impl<'a> Chip for Reader<'a> {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip other: Inc;
+    chip tick_mut: Inc;
+    other::b - tick_mut::a;
+});
+
+fn main() {}
@@ -0,0 +1,16 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Register {
+    #[pin(output)]
+    #[reset = 0xFF]
+    q: u8,
+}
+
+impl Chip for Register {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Cpu(
+    #[pin(output)]
+    #[bus]
+    [bool; 8],
+);
+
+impl Chip for Cpu {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+fn main() {}
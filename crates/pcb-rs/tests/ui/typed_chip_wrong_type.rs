@@ -0,0 +1,41 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Dec {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Dec {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a - 1;
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip i1: Inc;
+    chip i2: Inc;
+    i1::b - i2::a;
+});
+
+fn main() {
+    let _ = BoardBuilder::new().i1(Inc::default()).i2(Dec::default()).build();
+}
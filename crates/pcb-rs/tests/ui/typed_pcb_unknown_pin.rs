@@ -0,0 +1,30 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+pcb!(Inner {
+    chip i: Inc;
+    expose i::b as out;
+});
+
+// the pins of pcbs are known when their chips give their pins
+pcb!(Outer {
+    chip inner: Inner;
+    chip i: Inc;
+    inner::b - i::a;
+});
+
+fn main() {}
@@ -0,0 +1,36 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Inc {
+    #[pin(input)]
+    a: u8,
+    #[pin(output)]
+    b: u8,
+}
+
+impl Chip for Inc {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.b = self.a + 1;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Driver {
+    #[pin(output)]
+    out: Option<u8>,
+}
+
+impl Chip for Driver {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip d: Driver;
+    chip i: Inc;
+    d::out - i::a;
+});
+
+fn main() {}
@@ -0,0 +1,157 @@
+use pcb_rs::*;
+
+#[derive(Chip, Default)]
+struct Counter {
+    #[pin(output)]
+    count: u8,
+    #[pin(output)]
+    done: bool,
+}
+
+impl Chip for Counter {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        self.count += 1;
+        self.done = self.count >= 2;
+        Ok(())
+    }
+}
+
+#[derive(Chip, Default)]
+struct Sink {
+    #[pin(input)]
+    a: u8,
+    #[pin(input)]
+    en: Option<bool>,
+}
+
+impl Chip for Sink {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Board {
+    chip counter: Counter;
+    chip sink: Sink;
+    counter::count - sink::a;
+});
+
+fn output(pcb: &mut Board, ticks: usize) -> String {
+    let mut tracer = VcdTracer::new(Vec::new(), "board");
+    tracer.sample(pcb).unwrap();
+    for _ in 0..ticks {
+        pcb.tick();
+        tracer.sample(pcb).unwrap();
+    }
+    String::from_utf8(tracer.into_inner()).unwrap()
+}
+
+fn board() -> Board {
+    BoardBuilder::new()
+        .counter(Counter::default())
+        .sink(Sink::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn chips_get_their_own_scopes() {
+    let out = output(&mut board(), 0);
+    let expected = "\
+$timescale 1ns $end
+$scope module board $end
+$scope module counter $end
+$var wire 8 ! count $end
+$var wire 1 \" done $end
+$upscope $end
+$scope module sink $end
+$var wire 8 # a $end
+$var wire 1 $ en $end
+$upscope $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+b00000000 !
+0\"
+b00000000 #
+z$
+$end
+";
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn only_changed_values_are_written() {
+    let out = output(&mut board(), 3);
+    let changes = out.split("$dumpvars").nth(1).unwrap();
+    let changes: Vec<_> = changes
+        .lines()
+        .skip_while(|l| *l != "$end")
+        .skip(1)
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "#1",
+            "b00000001 !",
+            "b00000001 #",
+            "#2",
+            "b00000010 !",
+            "1\"",
+            "b00000010 #",
+            "#3",
+            "b00000011 !",
+            "b00000011 #",
+        ]
+    );
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct Opcode(u8);
+
+#[derive(Chip, Default)]
+struct Decoder {
+    #[pin(input)]
+    op: Opcode,
+    #[pin(output)]
+    valid: bool,
+}
+
+impl Chip for Decoder {
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
+}
+
+pcb!(Cpu {
+    chip counter: Counter;
+    chip decoder: Decoder;
+});
+
+fn cpu() -> Cpu {
+    CpuBuilder::new()
+        .counter(Counter::default())
+        .decoder(Decoder::default())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn unregistered_custom_types_are_errors() {
+    let mut tracer = VcdTracer::new(Vec::new(), "cpu");
+    match tracer.sample(&cpu()) {
+        Err(VcdError::UnregisteredType { path, .. }) => assert_eq!(path, "decoder/op"),
+        other => panic!("expected unregistered type error, got {:?}", other),
+    }
+}
+
+#[test]
+fn skipped_custom_types_are_left_out() {
+    let mut tracer = VcdTracer::new(Vec::new(), "cpu");
+    tracer.skip::<Opcode>();
+    tracer.sample(&cpu()).unwrap();
+    let out = String::from_utf8(tracer.into_inner()).unwrap();
+    assert!(out.contains("$var wire 1 # valid $end"));
+    assert!(!out.contains(" op "));
+}
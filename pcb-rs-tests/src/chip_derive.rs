@@ -1,4 +1,4 @@
-use pcb_rs::{Chip, ChipInterface};
+use pcb_rs::{Chip, ChipInterface, PinData};
 
 #[derive(Clone, Copy)]
 pub enum MemState {
@@ -49,15 +49,12 @@ fn main() {
     println!("{:#?}", p.get_pin_list());
     println!(
         "{:?}",
-        p.get_pin_value("data_bus")
-            .unwrap()
-            .downcast_ref::<Option<u8>>()
+        p.get_pin_value("data_bus").unwrap().get::<Option<u8>>()
     );
-    p.set_pin_value("data_bus", &Some(5_u8));
+    p.set_pin_value("data_bus", &Some(5_u8).to_pin_value())
+        .unwrap();
     println!(
         "{:?}",
-        p.get_pin_value("data_bus")
-            .unwrap()
-            .downcast_ref::<Option<u8>>()
+        p.get_pin_value("data_bus").unwrap().get::<Option<u8>>()
     );
 }
@@ -1,4 +1,4 @@
-use pcb_rs::{pcb, Chip};
+use pcb_rs::{pcb, Chip, SimError};
 
 #[derive(Chip, Default)]
 struct TestChip1 {
@@ -19,7 +19,9 @@ struct TestChip1 {
 }
 
 impl Chip for TestChip1 {
-    fn tick(&mut self) {}
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
 }
 
 #[derive(Chip, Default)]
@@ -41,7 +43,9 @@ struct TestChip2 {
 }
 
 impl Chip for TestChip2 {
-    fn tick(&mut self) {}
+    fn try_tick(&mut self) -> Result<(), SimError> {
+        Ok(())
+    }
 }
 
 pcb!(TestPCB{
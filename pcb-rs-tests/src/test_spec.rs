@@ -32,10 +32,11 @@ pub struct Processor {
 }
 
 impl HWModule for Processor {
-    fn tick(&mut self) {
+    fn try_tick(&mut self) -> Result<(), SimError> {
         // code to read instruction from cache, if
         // seg fault issue /  incomplete instruction,
         // set to read more instruction from mem
+        Ok(())
     }
 }
 
@@ -54,9 +55,10 @@ pub struct Memory {
 }
 
 impl HWModule for Memory {
-    fn tick(&mut self) {
+    fn try_tick(&mut self) -> Result<(), SimError> {
         // code to see if the state pin is set to state active
         // and the take appropriate action according to mode
+        Ok(())
     }
 }
 